@@ -0,0 +1,58 @@
+////////////////////////////////////////////////////////////////////////////////////////////////////
+//                                           Clipboard                                            //
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+// Replaces the system clipboard of a window, see Window::set_clipboard
+pub trait Clipboard {
+
+    fn get_text(&self) -> Option<String>;
+    fn set_text(&mut self, text: &str);
+
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+//                                        Memory Clipboard                                        //
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+// Clipboard which never leaves the process, used by headless windows
+#[derive(Clone, Default, Debug)]
+pub struct MemoryClipboard {
+    text: Option<String>,
+}
+
+impl MemoryClipboard {
+
+    pub fn new() -> MemoryClipboard {
+        return MemoryClipboard {
+            text: None,
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.text = None;
+    }
+
+}
+
+impl Clipboard for MemoryClipboard {
+
+    fn get_text(&self) -> Option<String> {
+        return self.text.clone();
+    }
+
+    fn set_text(&mut self, text: &str) {
+        self.text = Some(text.to_string());
+    }
+
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+//                                        Clipboard Action                                        //
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum ClipboardAction {
+    Copy,
+    Cut,
+    Paste,
+}
@@ -0,0 +1,823 @@
+use crate::window::WindowConfig;
+use lead_logger::{debug, warn};
+use serde::de::{DeserializeOwned, Deserializer, IgnoredAny, MapAccess, Visitor};
+use serde::{Serialize, Serializer};
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+use std::marker::PhantomData;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
+use toml::Value;
+use toml::value::Table;
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+//                                         Config Section                                         //
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+pub trait ConfigSection: DeserializeOwned + Serialize + Default {
+
+    // Name of the toml table holding the section, e.g. "window" for `[window]`
+    const NAME: &'static str;
+
+    fn validate(&self) -> Result<(), Vec<ValidationError>> {
+        return Ok(());
+    }
+
+}
+
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct ValidationError {
+    pub key: String,
+    pub message: String,
+}
+
+impl ValidationError {
+
+    pub fn new(key: &str, message: impl Into<String>) -> ValidationError {
+        return ValidationError {
+            key: key.to_string(),
+            message: message.into(),
+        }
+    }
+
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+//                                          Config Error                                          //
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+#[derive(Debug)]
+pub enum ConfigError {
+    Io {
+        path: PathBuf,
+        error: std::io::Error,
+    },
+    Parse {
+        path: PathBuf,
+        line: Option<usize>,
+        column: Option<usize>,
+        message: String,
+    },
+    Invalid {
+        path: PathBuf,
+        key: String,
+        line: Option<usize>,
+        column: Option<usize>,
+        message: String,
+    },
+}
+
+impl ConfigError {
+
+    pub fn get_path(&self) -> &Path {
+        return match self {
+            ConfigError::Io { path, .. } => path,
+            ConfigError::Parse { path, .. } => path,
+            ConfigError::Invalid { path, .. } => path,
+        }
+    }
+
+    // 1-based line and column of the error, if known
+    pub fn get_position(&self) -> Option<(usize, usize)> {
+        return match self {
+            ConfigError::Io { .. } => None,
+            ConfigError::Parse { line, column, .. } | ConfigError::Invalid { line, column, .. } => Some(((*line)?, column.unwrap_or(1))),
+        }
+    }
+
+}
+
+impl Display for ConfigError {
+
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        return match self {
+            ConfigError::Io { path, error } => write!(f, "Failed to read '{}': {}", path.display(), error),
+            ConfigError::Parse { path, message, .. } => write!(f, "Failed to parse '{}': {}", path.display(), message),
+            ConfigError::Invalid { path, key, message, .. } => match self.get_position() {
+                Some((line, column)) => write!(f, "Invalid value for '{}' in '{}' at line {} column {}: {}", key, path.display(), line, column, message),
+                None => write!(f, "Invalid value for '{}' in '{}': {}", key, path.display(), message),
+            },
+        }
+    }
+
+}
+
+impl std::error::Error for ConfigError {}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+//                                          Config File                                           //
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+pub struct ConfigFile {
+    path: PathBuf,
+    source: String,
+}
+
+impl ConfigFile {
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<ConfigFile, ConfigError> {
+        let path = path.as_ref().to_path_buf();
+        return match std::fs::read_to_string(&path) {
+            Ok(source) => Ok(ConfigFile { path, source }),
+            Err(error) => Err(ConfigError::Io { path, error }),
+        }
+    }
+
+    pub fn from_source<P: AsRef<Path>>(path: P, source: &str) -> ConfigFile {
+        return ConfigFile {
+            path: path.as_ref().to_path_buf(),
+            source: source.to_string(),
+        }
+    }
+
+    // Deserializes and validates a single section, `None` if the file has no such table
+    pub fn section<T: ConfigSection>(&self) -> Result<Option<T>, ConfigError> {
+        let mut deserializer = toml::Deserializer::new(&self.source);
+        let section = match deserializer.deserialize_any(SectionVisitor::<T>(T::NAME, PhantomData)) {
+            Ok(section) => section,
+            Err(err) => {
+                let (line, column) = match err.line_col() {
+                    Some((line, column)) => (Some(line + 1), Some(column + 1)),
+                    None => (None, None),
+                };
+                return Err(ConfigError::Parse {
+                    path: self.path.clone(),
+                    line,
+                    column,
+                    message: err.to_string(),
+                });
+            }
+        };
+
+        if let Some(section) = &section {
+            if let Err(mut errors) = section.validate() {
+                if !errors.is_empty() {
+                    let error = errors.remove(0);
+                    let position = self.locate(T::NAME, &error.key);
+                    return Err(ConfigError::Invalid {
+                        path: self.path.clone(),
+                        key: format!("{}.{}", T::NAME, error.key),
+                        line: position.map(|(line, _)| line),
+                        column: position.map(|(_, column)| column),
+                        message: error.message,
+                    });
+                }
+            }
+        }
+
+        return Ok(section);
+    }
+
+    // Best effort lookup of the 1-based position of `key = ...` inside the `[section]` table
+    fn locate(&self, section: &str, key: &str) -> Option<(usize, usize)> {
+        let mut current = String::new();
+        for (index, line) in self.source.lines().enumerate() {
+            let trimmed = line.trim_start();
+            if trimmed.starts_with('[') {
+                current = trimmed.trim_start_matches('[').split(']').next().unwrap_or("").trim().to_string();
+            } else if current == section {
+                if let Some(rest) = trimmed.strip_prefix(key) {
+                    if rest.trim_start().starts_with('=') {
+                        return Some((index + 1, line.len() - trimmed.len() + 1));
+                    }
+                }
+            }
+        }
+
+        return None;
+    }
+
+    /* ====================================== Getters ======================================= */
+
+    pub fn get_path(&self) -> &Path {
+        return &self.path;
+    }
+
+    pub fn get_source(&self) -> &str {
+        return &self.source;
+    }
+
+}
+
+struct SectionVisitor<T>(&'static str, PhantomData<T>);
+
+impl<'de, T: DeserializeOwned> Visitor<'de> for SectionVisitor<T> {
+
+    type Value = Option<T>;
+
+    fn expecting(&self, f: &mut Formatter) -> std::fmt::Result {
+        return write!(f, "a table containing a '{}' section", self.0);
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let mut section = None;
+        while let Some(key) = map.next_key::<String>()? {
+            if key == self.0 {
+                section = Some(map.next_value::<T>()?);
+            } else {
+                map.next_value::<IgnoredAny>()?;
+            }
+        }
+
+        return Ok(section);
+    }
+
+}
+
+// Loads a section, logging any error and falling back to the defaults
+pub fn load_section<T: ConfigSection, P: AsRef<Path>>(path: P) -> T {
+    let file = match ConfigFile::load(path) {
+        Ok(file) => file,
+        Err(err) => {
+            warn!("Config", "{}", err);
+            return T::default();
+        }
+    };
+
+    return match file.section::<T>() {
+        Ok(Some(section)) => section,
+        Ok(None) => {
+            warn!("Config", "Missing section '{}' in '{}', using defaults", T::NAME, file.get_path().display());
+            T::default()
+        }
+        Err(err) => {
+            warn!("Config", "{}, using defaults", err);
+            T::default()
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+//                                         Config Source                                          //
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub enum ConfigSource {
+    Default,
+    File(PathBuf),
+    User(PathBuf),
+    Environment(String),
+    CommandLine(String),
+}
+
+impl Display for ConfigSource {
+
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        return match self {
+            ConfigSource::Default => write!(f, "built-in defaults"),
+            ConfigSource::File(path) => write!(f, "file '{}'", path.display()),
+            ConfigSource::User(path) => write!(f, "user file '{}'", path.display()),
+            ConfigSource::Environment(var) => write!(f, "environment variable '{}'", var),
+            ConfigSource::CommandLine(arg) => write!(f, "command line argument '{}'", arg),
+        }
+    }
+
+}
+
+struct Layer {
+    source: ConfigSource,
+    values: Vec<(String, Value, ConfigSource)>,
+}
+
+#[derive(Clone, Debug)]
+pub struct Layered<T> {
+    pub value: T,
+    pub sources: BTreeMap<String, ConfigSource>,
+}
+
+impl<T> Layered<T> {
+
+    pub fn get_source(&self, key: &str) -> Option<&ConfigSource> {
+        return self.sources.get(key);
+    }
+
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+//                                         Config Loader                                          //
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+// Merges, from lowest to highest priority: the section defaults, the shipped file, the user file
+// in the platform config directory, `LEAD_<SECTION>_<KEY>` variables and `--section.key=value`
+// arguments. A layer producing an invalid section is ignored as a whole.
+pub struct ConfigLoader {
+    path: PathBuf,
+    app_name: String,
+    env_prefix: String,
+    env: BTreeMap<String, String>,
+    args: Vec<String>,
+    // Replaces the app directory in the platform config directory
+    user_dir: Option<PathBuf>,
+}
+
+impl ConfigLoader {
+
+    pub fn new<P: AsRef<Path>>(path: P) -> ConfigLoader {
+        return ConfigLoader {
+            path: path.as_ref().to_path_buf(),
+            app_name: "lead-engine".to_string(),
+            env_prefix: "LEAD".to_string(),
+            env: std::env::vars_os().filter_map(|(key, value)| Some((key.into_string().ok()?, value.into_string().ok()?))).collect(),
+            args: std::env::args().skip(1).collect(),
+            user_dir: None,
+        }
+    }
+
+    pub fn load<T: ConfigSection>(&self) -> Layered<T> {
+        let defaults = match Value::try_from(T::default()) {
+            Ok(Value::Table(table)) => table,
+            _ => Table::new(),
+        };
+
+        let mut layers = Vec::new();
+        match self.get_file_path() {
+            Some(path) => layers.push(Self::file_layer::<T>(&path, ConfigSource::File(path.clone()))),
+            None => warn!("Config", "Failed to find '{}' in the working directory or next to the executable", self.path.display()),
+        }
+        if let Some(path) = self.get_user_path() {
+            if path.is_file() {
+                layers.push(Self::file_layer::<T>(&path, ConfigSource::User(path.clone())));
+            }
+        }
+        layers.push(self.env_layer::<T>(&defaults));
+        layers.push(self.args_layer::<T>(&defaults));
+
+        let mut merged = defaults.clone();
+        let mut sources: BTreeMap<String, ConfigSource> = defaults.keys()
+            .map(|key| (format!("{}.{}", T::NAME, key), ConfigSource::Default))
+            .collect();
+        let mut value = T::default();
+
+        for layer in layers.into_iter().filter(|layer| !layer.values.is_empty()) {
+            let mut candidate = merged.clone();
+            for (key, item, _) in layer.values.iter() {
+                candidate.insert(key.clone(), item.clone());
+            }
+
+            let result = T::deserialize(Value::Table(candidate.clone()))
+                .map_err(|err| err.to_string())
+                .and_then(|section| match section.validate() {
+                    Ok(()) => Ok(section),
+                    Err(errors) => Err(errors.iter().map(|err| format!("'{}' {}", err.key, err.message)).collect::<Vec<_>>().join(", ")),
+                });
+
+            match result {
+                Ok(section) => {
+                    for (key, _, source) in layer.values.into_iter() {
+                        sources.insert(format!("{}.{}", T::NAME, key), source);
+                    }
+                    merged = candidate;
+                    value = section;
+                }
+                Err(err) => warn!("Config", "Ignoring overrides of '{}' from {}: {}", T::NAME, layer.source, err),
+            }
+        }
+
+        return Layered {
+            value,
+            sources,
+        }
+    }
+
+    fn file_layer<T: ConfigSection>(path: &Path, source: ConfigSource) -> Layer {
+        let mut layer = Layer {
+            source: source.clone(),
+            values: Vec::new(),
+        };
+
+        // Parsing the section on its own first reports errors with their position in the file
+        let file = match ConfigFile::load(path) {
+            Ok(file) => file,
+            Err(err) => {
+                warn!("Config", "{}", err);
+                return layer;
+            }
+        };
+        if let Err(err) = file.section::<T>() {
+            warn!("Config", "{}", err);
+            return layer;
+        }
+
+        if let Ok(Value::Table(mut root)) = file.get_source().parse::<Value>() {
+            if let Some(Value::Table(table)) = root.remove(T::NAME) {
+                debug!("Config", "Loaded section '{}' from {}", T::NAME, source);
+                layer.values = table.into_iter().map(|(key, value)| (key, value, source.clone())).collect();
+            }
+        }
+
+        return layer;
+    }
+
+    fn env_layer<T: ConfigSection>(&self, defaults: &Table) -> Layer {
+        let mut layer = Layer {
+            source: ConfigSource::Environment(format!("{}_{}_*", self.env_prefix, env_name(T::NAME))),
+            values: Vec::new(),
+        };
+
+        for key in defaults.keys() {
+            let var = format!("{}_{}_{}", self.env_prefix, env_name(T::NAME), env_name(key));
+            if let Some(raw) = self.env.get(&var) {
+                layer.values.push((key.clone(), parse_raw(raw), ConfigSource::Environment(var)));
+            }
+        }
+
+        return layer;
+    }
+
+    fn args_layer<T: ConfigSection>(&self, defaults: &Table) -> Layer {
+        let mut layer = Layer {
+            source: ConfigSource::CommandLine(format!("--{}.*", T::NAME)),
+            values: Vec::new(),
+        };
+
+        for arg in self.args.iter() {
+            let (name, raw) = match arg.strip_prefix("--").and_then(|arg| arg.split_once('=')) {
+                Some(pair) => pair,
+                None => continue,
+            };
+            let key = match name.split_once('.') {
+                Some((section, key)) if section == T::NAME => key,
+                _ => continue,
+            };
+
+            if defaults.contains_key(key) {
+                layer.values.push((key.to_string(), parse_raw(raw), ConfigSource::CommandLine(arg.clone())));
+            } else {
+                warn!("Config", "Unknown property '{}' in command line argument '{}'", name, arg);
+            }
+        }
+
+        return layer;
+    }
+
+    /* ====================================== Getters ======================================= */
+
+    // Relative paths are looked up in the working directory first, then next to the executable
+    // and in its parent directories
+    pub fn get_file_path(&self) -> Option<PathBuf> {
+        if self.path.is_absolute() || self.path.is_file() {
+            return Some(self.path.clone()).filter(|path| path.is_file());
+        }
+
+        let exe = std::env::current_exe().ok()?;
+        return exe.ancestors().skip(1).map(|dir| dir.join(&self.path)).find(|path| path.is_file());
+    }
+
+    pub fn get_user_path(&self) -> Option<PathBuf> {
+        let file_name = self.path.file_name()?;
+        let dir = match &self.user_dir {
+            Some(dir) => dir.clone(),
+            None => user_config_dir()?.join(&self.app_name),
+        };
+        return Some(dir.join(file_name));
+    }
+
+    // Location for state written by the engine itself, like the last window geometry
+    pub fn get_data_path(&self, file_name: &str) -> Option<PathBuf> {
+        return Some(user_data_dir()?.join(&self.app_name).join(file_name));
+    }
+
+    pub fn get_app_name(&self) -> &str {
+        return &self.app_name;
+    }
+
+    /* ====================================== Setters ======================================= */
+
+    pub fn set_app_name(&mut self, app_name: &str) {
+        self.app_name = app_name.to_string();
+    }
+
+    pub fn set_env_prefix(&mut self, env_prefix: &str) {
+        self.env_prefix = env_prefix.to_string();
+    }
+
+    // Variables used instead of the environment of the process
+    pub fn set_env(&mut self, env: BTreeMap<String, String>) {
+        self.env = env;
+    }
+
+    pub fn set_args(&mut self, args: Vec<String>) {
+        self.args = args;
+    }
+
+    pub fn set_user_dir<P: AsRef<Path>>(&mut self, user_dir: P) {
+        self.user_dir = Some(user_dir.as_ref().to_path_buf());
+    }
+
+}
+
+fn env_name(name: &str) -> String {
+    return name.to_uppercase().replace('-', "_");
+}
+
+// Values given as text are read as toml when possible, `1280` is an integer and `vsync` a string
+fn parse_raw(raw: &str) -> Value {
+    if let Ok(Value::Table(mut table)) = format!("value = {}", raw).parse::<Value>() {
+        if let Some(value) = table.remove("value") {
+            return value;
+        }
+    }
+    return Value::String(raw.to_string());
+}
+
+pub fn user_config_dir() -> Option<PathBuf> {
+    if cfg!(target_os = "windows") {
+        return std::env::var_os("APPDATA").map(PathBuf::from);
+    } else if cfg!(target_os = "macos") {
+        return std::env::var_os("HOME").map(|home| PathBuf::from(home).join("Library").join("Application Support"));
+    }
+
+    if let Some(dir) = std::env::var_os("XDG_CONFIG_HOME").filter(|dir| !dir.is_empty()) {
+        return Some(PathBuf::from(dir));
+    }
+    return std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config"));
+}
+
+pub fn user_data_dir() -> Option<PathBuf> {
+    if cfg!(target_os = "windows") || cfg!(target_os = "macos") {
+        return user_config_dir();
+    }
+
+    if let Some(dir) = std::env::var_os("XDG_DATA_HOME").filter(|dir| !dir.is_empty()) {
+        return Some(PathBuf::from(dir));
+    }
+    return std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".local").join("share"));
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+//                                         Config Watcher                                         //
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+// Polls the modification time of the watched files, at most once per interval
+pub struct ConfigWatcher {
+    files: Vec<(PathBuf, Option<SystemTime>)>,
+    interval: Duration,
+    last_poll: Instant,
+    enabled: bool,
+}
+
+impl ConfigWatcher {
+
+    pub fn new(interval: Duration) -> ConfigWatcher {
+        return ConfigWatcher {
+            files: Vec::new(),
+            interval,
+            last_poll: Instant::now(),
+            enabled: true,
+        }
+    }
+
+    pub fn watch<P: AsRef<Path>>(&mut self, path: P) {
+        let path = path.as_ref().to_path_buf();
+        if !self.files.iter().any(|(file, _)| *file == path) {
+            let modified = modified_time(&path);
+            self.files.push((path, modified));
+        }
+    }
+
+    pub fn unwatch<P: AsRef<Path>>(&mut self, path: P) {
+        self.files.retain(|(file, _)| file != path.as_ref());
+    }
+
+    // Files created, modified or removed since the last poll
+    pub fn poll(&mut self) -> Vec<PathBuf> {
+        if !self.enabled || self.last_poll.elapsed() < self.interval {
+            return Vec::new();
+        }
+        self.last_poll = Instant::now();
+
+        let mut changed = Vec::new();
+        for (path, last) in self.files.iter_mut() {
+            let modified = modified_time(path);
+            if modified != *last {
+                *last = modified;
+                changed.push(path.clone());
+            }
+        }
+
+        return changed;
+    }
+
+    /* ====================================== Getters ======================================= */
+
+    pub fn get_interval(&self) -> Duration {
+        return self.interval;
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        return self.enabled;
+    }
+
+    /* ====================================== Setters ======================================= */
+
+    pub fn set_interval(&mut self, interval: Duration) {
+        self.interval = interval;
+    }
+
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+    }
+
+}
+
+fn modified_time(path: &Path) -> Option<SystemTime> {
+    return std::fs::metadata(path).and_then(|metadata| metadata.modified()).ok();
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+//                                         Engine Config                                          //
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+pub const ENGINE_CONFIG: &str = "res/engine/window.toml";
+
+#[derive(Clone, Debug, Default)]
+pub struct EngineConfig {
+    pub window: WindowConfig,
+    pub sources: BTreeMap<String, ConfigSource>,
+}
+
+impl EngineConfig {
+
+    pub fn load<P: AsRef<Path>>(path: P) -> EngineConfig {
+        return EngineConfig::load_with(&ConfigLoader::new(path));
+    }
+
+    pub fn load_with(loader: &ConfigLoader) -> EngineConfig {
+        let window = loader.load::<WindowConfig>();
+
+        let mut sources = BTreeMap::new();
+        sources.extend(window.sources);
+
+        return EngineConfig {
+            window: window.value,
+            sources,
+        }
+    }
+
+    // Layer which provided the given `section.key`, e.g. "window.width"
+    pub fn get_source(&self, key: &str) -> Option<&ConfigSource> {
+        return self.sources.get(key);
+    }
+
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+//                                            Helpers                                             //
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+// Optional limit written either as a non negative integer or as "unset"
+pub fn deserialize_limit<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<u32>, D::Error> {
+    struct LimitVisitor;
+
+    impl<'de> Visitor<'de> for LimitVisitor {
+
+        type Value = Option<u32>;
+
+        fn expecting(&self, f: &mut Formatter) -> std::fmt::Result {
+            return write!(f, "a non negative integer or \"unset\"");
+        }
+
+        fn visit_i64<E: serde::de::Error>(self, value: i64) -> Result<Self::Value, E> {
+            return match u32::try_from(value) {
+                Ok(value) => Ok(Some(value)),
+                Err(_) => Err(E::invalid_value(serde::de::Unexpected::Signed(value), &self)),
+            }
+        }
+
+        fn visit_str<E: serde::de::Error>(self, value: &str) -> Result<Self::Value, E> {
+            if value.eq_ignore_ascii_case("unset") {
+                return Ok(None);
+            }
+            return Err(E::invalid_value(serde::de::Unexpected::Str(value), &self));
+        }
+
+    }
+
+    return deserializer.deserialize_any(LimitVisitor);
+}
+
+pub fn serialize_limit<S: Serializer>(limit: &Option<u32>, serializer: S) -> Result<S::Ok, S::Error> {
+    return match limit {
+        Some(value) => serializer.serialize_u32(*value),
+        None => serializer.serialize_str("unset"),
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+//                                             Tests                                              //
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+
+    #[derive(Clone, PartialEq, Debug, Deserialize, Serialize)]
+    #[serde(default)]
+    struct TestConfig {
+        width: u32,
+        title: String,
+        vsync: bool,
+        scale: f64,
+    }
+
+    impl Default for TestConfig {
+
+        fn default() -> Self {
+            return TestConfig {
+                width: 800,
+                title: "Lead".to_string(),
+                vsync: true,
+                scale: 1.0,
+            }
+        }
+
+    }
+
+    impl ConfigSection for TestConfig {
+
+        const NAME: &'static str = "test";
+
+        fn validate(&self) -> Result<(), Vec<ValidationError>> {
+            if self.width == 0 {
+                return Err(vec![ValidationError::new("width", "must be positive")]);
+            }
+            return Ok(());
+        }
+
+    }
+
+    // Writes the shipped and user files into their own directory
+    fn loader(name: &str, file: &str, user: Option<&str>, env: &[(&str, &str)], args: &[&str]) -> ConfigLoader {
+        let dir = std::env::temp_dir().join(format!("lead-config-{}-{}", name, std::process::id()));
+        std::fs::create_dir_all(dir.join("user")).unwrap();
+        std::fs::write(dir.join("test.toml"), file).unwrap();
+        if let Some(user) = user {
+            std::fs::write(dir.join("user").join("test.toml"), user).unwrap();
+        }
+
+        let mut loader = ConfigLoader::new(dir.join("test.toml"));
+        loader.set_user_dir(dir.join("user"));
+        loader.set_env(env.iter().map(|(key, value)| (key.to_string(), value.to_string())).collect());
+        loader.set_args(args.iter().map(|arg| arg.to_string()).collect());
+        return loader;
+    }
+
+    #[test]
+    fn layers_override_each_other_by_priority() {
+        let loader = loader(
+            "priority",
+            "[test]\nwidth = 1024\ntitle = \"File\"\n",
+            Some("[test]\ntitle = \"User\"\nwidth = 1280\n"),
+            &[("LEAD_TEST_VSYNC", "false"), ("LEAD_TEST_WIDTH", "1600"), ("OTHER_TEST_SCALE", "2")],
+            &["--test.width=1920", "--other.width=1"],
+        );
+
+        let config = loader.load::<TestConfig>();
+        assert_eq!(config.value, TestConfig { width: 1920, title: "User".to_string(), vsync: false, scale: 1.0 });
+        assert_eq!(config.get_source("test.width"), Some(&ConfigSource::CommandLine("--test.width=1920".to_string())));
+        assert_eq!(config.get_source("test.vsync"), Some(&ConfigSource::Environment("LEAD_TEST_VSYNC".to_string())));
+        assert_eq!(config.get_source("test.title"), Some(&ConfigSource::User(loader.get_user_path().unwrap())));
+        assert_eq!(config.get_source("test.scale"), Some(&ConfigSource::Default));
+    }
+
+    #[test]
+    fn invalid_layers_are_ignored_as_a_whole() {
+        let loader = loader(
+            "invalid",
+            "[test]\nwidth = 0\ntitle = \"File\"\n",
+            Some("[test]\nwidth = \"wide\"\n"),
+            &[("LEAD_TEST_VSYNC", "often")],
+            &["--test.title=Command Line", "--test.scale=2"],
+        );
+
+        let config = loader.load::<TestConfig>();
+        assert_eq!(config.value, TestConfig { width: 800, title: "Command Line".to_string(), vsync: true, scale: 2.0 });
+        assert_eq!(config.get_source("test.width"), Some(&ConfigSource::Default));
+        assert_eq!(config.get_source("test.vsync"), Some(&ConfigSource::Default));
+    }
+
+    #[test]
+    fn missing_files_fall_back_to_the_defaults() {
+        let dir = std::env::temp_dir().join("lead-config-missing");
+        let mut loader = ConfigLoader::new(dir.join("test.toml"));
+        loader.set_user_dir(dir.join("user"));
+        loader.set_env(BTreeMap::new());
+        loader.set_args(Vec::new());
+
+        let config = loader.load::<TestConfig>();
+        assert_eq!(config.value, TestConfig::default());
+        assert_eq!(config.sources.len(), 4);
+        assert!(config.sources.values().all(|source| *source == ConfigSource::Default));
+    }
+
+    #[test]
+    fn raw_values_are_read_as_toml_when_possible() {
+        assert_eq!(parse_raw("1280"), Value::Integer(1280));
+        assert_eq!(parse_raw("1.5"), Value::Float(1.5));
+        assert_eq!(parse_raw("false"), Value::Boolean(false));
+        assert_eq!(parse_raw("\"quoted\""), Value::String("quoted".to_string()));
+        assert_eq!(parse_raw("vsync"), Value::String("vsync".to_string()));
+    }
+
+}
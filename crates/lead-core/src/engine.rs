@@ -0,0 +1,599 @@
+use crate::config::{ConfigFile, ConfigLoader, ConfigWatcher, EngineConfig, ENGINE_CONFIG};
+use crate::event::EventBus;
+use crate::init_glfw;
+use crate::input::{Input, InputEvent};
+use crate::input::action_map::ActionMap;
+use crate::input::combo::{ComboDetector, ComboEvent};
+use crate::input::text::TextBuffer;
+use crate::state::{StateManager, TState};
+use crate::window::{UpdateCap, Window, WindowConfig, WindowId, GEOMETRY_FILE};
+use std::collections::BTreeMap;
+use lead_logger::{debug, info, warn, Logger, LogLevel};
+use lead_mem::lifecycle::LifecycleRegistry;
+use lead_mem::resources::Resources;
+use lead_mem::tracking::{self, MemoryReport};
+use std::any::TypeId;
+use std::time::{Duration, Instant};
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+//                                            Context                                             //
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+pub struct Context {
+    config: EngineConfig,
+    config_loader: ConfigLoader,
+    config_watcher: ConfigWatcher,
+    window: Window,
+    input: Input,
+    actions: ActionMap,
+    combos: ComboDetector,
+    // Additional windows, each with its own input state
+    windows: BTreeMap<WindowId, (Window, Input)>,
+    next_window_id: usize,
+    events: EventBus,
+    // Used by the engine instead of the global logger, so each engine has its own level
+    logger: Logger,
+    resources: Resources,
+    lifecycle: LifecycleRegistry,
+    // Allocations of the last frame, when the tracking allocator is installed
+    memory_report: Option<MemoryReport>,
+    requested_state: Option<(TypeId, &'static str)>,
+}
+
+impl Context {
+
+    fn new(headless: bool) -> Context {
+        let glfw = if headless { None } else { Some(init_glfw()) };
+        let config_loader = ConfigLoader::new(ENGINE_CONFIG);
+        let config = EngineConfig::load_with(&config_loader);
+
+        let mut config_watcher = ConfigWatcher::new(Duration::from_secs(1));
+        if let Some(path) = config_loader.get_file_path() {
+            config_watcher.watch(path);
+        }
+        if let Some(path) = config_loader.get_user_path() {
+            config_watcher.watch(path);
+        }
+
+        let mut window = Window::new(WindowId::PRIMARY, glfw, &config.window);
+        if config.window.remember_geometry {
+            if let Some(path) = config_loader.get_data_path(GEOMETRY_FILE) {
+                window.restore_geometry(path);
+            }
+        }
+
+        let mut input = Input::new();
+        input.set_glfw(window.get_glfw());
+
+        return Context {
+            window,
+            config,
+            config_loader,
+            config_watcher,
+            input,
+            actions: ActionMap::new(),
+            combos: ComboDetector::new(),
+            windows: BTreeMap::new(),
+            next_window_id: 1,
+            events: EventBus::new(),
+            logger: Logger::new(),
+            resources: Resources::new(),
+            lifecycle: LifecycleRegistry::new(),
+            memory_report: None,
+            requested_state: None,
+        }
+    }
+
+    pub(crate) fn poll_config(&mut self) {
+        let changed = self.config_watcher.poll();
+        if changed.is_empty() {
+            return;
+        }
+
+        // An edit which does not parse or validate is rejected, the current config stays in place
+        for path in changed.iter().filter(|path| path.is_file()) {
+            if let Err(err) = ConfigFile::load(path).and_then(|file| file.section::<WindowConfig>()) {
+                warn!(logger: self.logger, "Config", "Rejected changes: {}", err);
+                return;
+            }
+        }
+
+        info!(logger: self.logger, "Config", "Reloading configuration after changes to {:?}", changed);
+        self.reload_config();
+    }
+
+    pub(crate) fn save_geometry(&self) {
+        if self.config.window.remember_geometry {
+            if let Some(path) = self.config_loader.get_data_path(GEOMETRY_FILE) {
+                self.window.save_geometry(path);
+            }
+        }
+    }
+
+    pub fn reload_config(&mut self) {
+        let config = EngineConfig::load_with(&self.config_loader);
+        self.window.apply_config_changes(&self.config.window, &config.window);
+        self.config = config;
+    }
+
+    // Opens an additional window, its OpenGL objects are shared with the primary window
+    pub fn create_window(&mut self, config: &WindowConfig) -> WindowId {
+        let id = WindowId(self.next_window_id);
+        self.next_window_id += 1;
+
+        let window = self.window.new_shared(id, config);
+        self.window.make_current();
+        let mut input = Input::new();
+        input.set_glfw(window.get_glfw());
+        self.windows.insert(id, (window, input));
+        return id;
+    }
+
+    // The primary window can not be destroyed, it has to be closed instead
+    pub fn destroy_window(&mut self, id: WindowId) -> bool {
+        return self.windows.remove(&id).is_some();
+    }
+
+    // Returns the delta of the frame, which is the recorded one while replaying input
+    pub(crate) fn process_events(&mut self, delta: f64) -> f64 {
+        // Polling once for every window keeps the events of all of them in the same frame
+        self.window.poll_events();
+        self.window.process_events(&mut self.input, Some(&mut self.events));
+
+        // Gamepads are shared by every window, their state lives in the primary input
+        let time = self.input.get_time();
+        for event in self.input.gamepads().poll() {
+            if let Some(event) = self.input.handle_event(time, InputEvent::Gamepad(event)) {
+                self.events.publish(event);
+            }
+        }
+
+        let (delta, replayed) = self.input.end_frame(delta);
+        for event in replayed {
+            self.events.publish(event);
+        }
+
+        self.actions.update(&self.input);
+        self.combos.update(&self.input, delta);
+        for name in self.combos.get_completed() {
+            self.events.publish(ComboEvent(name.clone()));
+        }
+        self.input.contexts_mut().update();
+
+        // Gamepads are only tracked by the primary input, replays and recordings of additional
+        // windows follow the delta of the frame
+        for (window, input) in self.windows.values_mut() {
+            window.process_events(input, Some(&mut self.events));
+            let (_, replayed) = input.end_frame(delta);
+            for event in replayed {
+                window.publish(&mut self.events, event);
+            }
+            input.contexts_mut().update();
+        }
+        return delta;
+    }
+
+    pub(crate) fn resolve_close_requests(&mut self) {
+        self.window.resolve_close_request();
+        for (window, _) in self.windows.values_mut() {
+            window.resolve_close_request();
+        }
+
+        // Additional windows are destroyed once closed
+        self.windows.retain(|_, (window, _)| !window.should_close());
+    }
+
+    pub(crate) fn swap_buffers(&mut self) {
+        self.window.swap_buffers();
+        for (window, _) in self.windows.values_mut() {
+            window.swap_buffers();
+        }
+    }
+
+    // The state is opened once the current callback returns
+    pub fn open_state<State: TState + 'static>(&mut self) {
+        self.requested_state = Some((TypeId::of::<State>(), std::any::type_name::<State>()));
+    }
+
+    // Applies the text input of the frame to the buffer using the clipboard of the primary window,
+    // returns whether the text changed
+    pub fn edit_text(&mut self, buffer: &mut TextBuffer) -> bool {
+        return buffer.update(&self.input, &mut self.window);
+    }
+
+    /* ====================================== Getters ======================================= */
+
+    pub fn config(&self) -> &EngineConfig {
+        return &self.config;
+    }
+
+    pub fn config_watcher(&self) -> &ConfigWatcher {
+        return &self.config_watcher;
+    }
+
+    pub fn config_watcher_mut(&mut self) -> &mut ConfigWatcher {
+        return &mut self.config_watcher;
+    }
+
+    pub fn window(&self) -> &Window {
+        return &self.window;
+    }
+
+    pub fn window_mut(&mut self) -> &mut Window {
+        return &mut self.window;
+    }
+
+    pub fn get_window(&self, id: WindowId) -> Option<&Window> {
+        if id.is_primary() {
+            return Some(&self.window);
+        }
+        return self.windows.get(&id).map(|(window, _)| window);
+    }
+
+    pub fn get_window_mut(&mut self, id: WindowId) -> Option<&mut Window> {
+        if id.is_primary() {
+            return Some(&mut self.window);
+        }
+        return self.windows.get_mut(&id).map(|(window, _)| window);
+    }
+
+    pub fn get_window_ids(&self) -> Vec<WindowId> {
+        return std::iter::once(WindowId::PRIMARY).chain(self.windows.keys().copied()).collect();
+    }
+
+    pub fn input(&self) -> &Input {
+        return &self.input;
+    }
+
+    pub fn input_mut(&mut self) -> &mut Input {
+        return &mut self.input;
+    }
+
+    pub fn actions(&self) -> &ActionMap {
+        return &self.actions;
+    }
+
+    pub fn actions_mut(&mut self) -> &mut ActionMap {
+        return &mut self.actions;
+    }
+
+    pub fn combos(&self) -> &ComboDetector {
+        return &self.combos;
+    }
+
+    pub fn combos_mut(&mut self) -> &mut ComboDetector {
+        return &mut self.combos;
+    }
+
+    pub fn get_input(&self, id: WindowId) -> Option<&Input> {
+        if id.is_primary() {
+            return Some(&self.input);
+        }
+        return self.windows.get(&id).map(|(_, input)| input);
+    }
+
+    pub fn get_input_mut(&mut self, id: WindowId) -> Option<&mut Input> {
+        if id.is_primary() {
+            return Some(&mut self.input);
+        }
+        return self.windows.get_mut(&id).map(|(_, input)| input);
+    }
+
+    pub fn events(&self) -> &EventBus {
+        return &self.events;
+    }
+
+    pub fn events_mut(&mut self) -> &mut EventBus {
+        return &mut self.events;
+    }
+
+    pub fn logger(&self) -> &Logger {
+        return &self.logger;
+    }
+
+    pub fn logger_mut(&mut self) -> &mut Logger {
+        return &mut self.logger;
+    }
+
+    pub fn resources(&self) -> &Resources {
+        return &self.resources;
+    }
+
+    pub fn resources_mut(&mut self) -> &mut Resources {
+        return &mut self.resources;
+    }
+
+    pub fn lifecycle(&self) -> &LifecycleRegistry {
+        return &self.lifecycle;
+    }
+
+    pub fn lifecycle_mut(&mut self) -> &mut LifecycleRegistry {
+        return &mut self.lifecycle;
+    }
+
+    pub fn memory_report(&self) -> Option<&MemoryReport> {
+        return self.memory_report.as_ref();
+    }
+
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+//                                             Engine                                             //
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+pub struct Engine {
+    context: Context,
+    state_manager: StateManager,
+}
+
+impl Engine {
+
+    pub fn new() -> Engine {
+        return Engine::create(false);
+    }
+
+    // Engine without any native window, used to drive states from tests
+    pub fn headless() -> Engine {
+        return Engine::create(true);
+    }
+
+    fn create(headless: bool) -> Engine {
+        let mut context = Context::new(headless);
+        let state_manager = StateManager::new(&mut context);
+
+        return Engine {
+            context,
+            state_manager,
+        }
+    }
+
+    pub fn register<State: TState + 'static>(&mut self, state: State) {
+        self.state_manager.register(&mut self.context, state);
+        self.apply_requests();
+    }
+
+    pub fn open<State: TState + 'static>(&mut self) {
+        self.state_manager.open::<State>(&mut self.context);
+        self.apply_requests();
+    }
+
+    pub fn init(&mut self) {
+        self.state_manager.init(&mut self.context);
+        self.apply_requests();
+    }
+
+    // Runs a single frame: events, state update and buffer swap
+    pub fn step(&mut self, delta: f64) {
+        // Config hot reload
+        self.context.poll_config();
+
+        // Events of the previous frame expire
+        self.context.events.update();
+
+        // Window Update
+        let delta = self.context.process_events(delta);
+
+        // State update
+        self.state_manager.update(&mut self.context, delta);
+        self.apply_requests();
+
+        // Close requests which were not vetoed by the states
+        self.context.resolve_close_requests();
+
+        // Swap Buffers
+        self.context.swap_buffers();
+
+        // Memory report
+        if tracking::is_tracking() {
+            self.context.memory_report = Some(tracking::end_frame());
+        }
+    }
+
+    pub fn dispose(&mut self) {
+        self.state_manager.dispose(&mut self.context);
+        // States can not be opened anymore, requests made while closing are reported instead
+        self.apply_requests();
+        self.context.save_geometry();
+
+        let report = self.context.lifecycle.report();
+        if !report.is_clean() {
+            warn!(logger: self.context.logger, "Lifecycle", "{}", report);
+        }
+
+        if tracking::is_tracking() {
+            debug!(logger: self.context.logger, "Memory", "{}", tracking::report());
+        }
+    }
+
+    pub fn run(&mut self) {
+        self.context.logger.set_level(LogLevel::Debug);
+
+        // Initialize
+        self.init();
+
+        // Loop
+        let mut last = Instant::now();
+        while !self.context.window.should_close() {
+            let delta = last.elapsed().as_secs_f64();
+            let can_update = match self.context.window.get_update_cap() {
+                UpdateCap::Cap(cap) => delta >= 1.0 / cap as f64,
+                _ => true
+            };
+
+            if can_update {
+                self.step(delta);
+                last = Instant::now();
+            }
+        }
+
+        // Dispose
+        self.dispose();
+    }
+
+    fn apply_requests(&mut self) {
+        while let Some((type_id, name)) = self.context.requested_state.take() {
+            self.state_manager.open_type(&mut self.context, type_id, name);
+        }
+    }
+
+    /* ====================================== Getters ======================================= */
+
+    pub fn context(&self) -> &Context {
+        return &self.context;
+    }
+
+    pub fn context_mut(&mut self) -> &mut Context {
+        return &mut self.context;
+    }
+
+    pub fn state_manager(&self) -> &StateManager {
+        return &self.state_manager;
+    }
+
+    pub fn state_manager_mut(&mut self) -> &mut StateManager {
+        return &mut self.state_manager;
+    }
+
+}
+
+impl Default for Engine {
+
+    fn default() -> Self {
+        return Engine::new();
+    }
+
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+//                                             Tests                                              //
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event::EventReader;
+    use crate::input::{Action, Key};
+    use crate::input::action_map::Binding;
+    use crate::input::combo::Combo;
+    use crate::input::context::InputBlock;
+    use crate::window::WindowTagged;
+
+    // A menu drawn over the game, which keeps the keyboard for itself
+    struct Menu;
+
+    impl TState for Menu {
+        fn init(&mut self, _: &mut Context) {}
+        fn open(&mut self, _: &mut Context) {}
+        fn update(&mut self, ctx: &mut Context, _: f64) {
+            ctx.input_mut().contexts_mut().consume("menu", InputBlock::Keyboard);
+        }
+        fn close(&mut self, _: &mut Context) {}
+        fn dispose(&mut self, _: &mut Context) {}
+    }
+
+    #[test]
+    fn consumed_inputs_do_not_reach_lower_actions_and_combos() {
+        let mut engine = Engine::headless();
+        let ctx = engine.context_mut();
+        let contexts = ctx.input_mut().contexts_mut();
+        contexts.add("menu", 10);
+        contexts.add("game", 0);
+        contexts.activate("menu");
+        contexts.activate("game");
+        ctx.actions_mut().bind("jump", Binding::key(Key::Space));
+        ctx.actions_mut().set_context("jump", Some("game"));
+        ctx.actions_mut().bind("confirm", Binding::key(Key::Space));
+        ctx.combos_mut().add("charge", Combo::hold(Binding::key(Key::Space), 0.0));
+        ctx.combos_mut().set_context("charge", Some("game"));
+
+        engine.register(Menu);
+        engine.open::<Menu>();
+        engine.init();
+        engine.step(0.1);
+
+        engine.context_mut().input_mut().press_key(Key::Space);
+        engine.step(0.1);
+        let ctx = engine.context();
+        assert_eq!(ctx.actions().get_action("jump"), Action::Up);
+        assert_eq!(ctx.actions().get_action("confirm"), Action::Pressed);
+        assert!(!ctx.combos().is_completed("charge"));
+
+        // The menu closes, the game sees the held key from the next frame on
+        engine.context_mut().input_mut().contexts_mut().deactivate("menu");
+        engine.step(0.1);
+        let ctx = engine.context();
+        assert_eq!(ctx.actions().get_action("jump"), Action::Pressed);
+        assert!(ctx.combos().is_completed("charge"));
+    }
+
+    #[test]
+    fn capturing_the_mouse_resets_the_motion_origin() {
+        let mut engine = Engine::headless();
+        engine.context_mut().input_mut().move_mouse(100.0, 100.0);
+        engine.step(0.1);
+        engine.context_mut().input_mut().move_mouse(110.0, 90.0);
+        engine.step(0.1);
+        assert_eq!(engine.context().input().mouse().get_motion_x(), 10.0);
+
+        // The captured cursor starts somewhere else, the first move is not motion
+        engine.context_mut().window_mut().capture_mouse();
+        engine.context_mut().input_mut().move_mouse(960.0, 540.0);
+        engine.step(0.1);
+        assert_eq!(engine.context().input().mouse().get_motion_x(), 0.0);
+        assert_eq!(engine.context().input().mouse().get_motion_y(), 0.0);
+
+        engine.context_mut().input_mut().move_mouse(965.0, 530.0);
+        engine.step(0.1);
+        assert_eq!(engine.context().input().mouse().get_motion_x(), 5.0);
+        assert_eq!(engine.context().input().mouse().get_motion_y(), -10.0);
+
+        engine.context_mut().window_mut().release_mouse();
+        engine.context_mut().input_mut().move_mouse(110.0, 90.0);
+        engine.step(0.1);
+        assert_eq!(engine.context().input().mouse().get_motion_x(), 0.0);
+    }
+
+    #[test]
+    fn additional_windows_publish_tagged_events() {
+        let mut engine = Engine::headless();
+        let id = engine.context_mut().create_window(&WindowConfig::default());
+        let mut primary: EventReader<InputEvent> = engine.context_mut().events_mut().reader();
+        let mut tagged: EventReader<WindowTagged<InputEvent>> = engine.context_mut().events_mut().reader();
+
+        engine.context_mut().get_input_mut(id).unwrap().press_key(Key::A);
+        engine.step(0.25);
+
+        let events = engine.context().events();
+        assert_eq!(events.read(&mut primary).count(), 0);
+        let published: Vec<&WindowTagged<InputEvent>> = events.read(&mut tagged).collect();
+        assert_eq!(published.len(), 1);
+        assert_eq!(published[0].0, id);
+        assert!(matches!(published[0].1, InputEvent::Key(Key::A, _, Action::Pressed, _)));
+        assert_eq!(engine.context().get_input(id).unwrap().keyboard().get_key(Key::A), Action::Pressed);
+    }
+
+    #[test]
+    fn additional_window_input_time_advances() {
+        let mut engine = Engine::headless();
+        let id = engine.context_mut().create_window(&WindowConfig::default());
+        engine.step(0.25);
+        engine.step(0.25);
+
+        let input = engine.context_mut().get_input_mut(id).unwrap();
+        input.press_key(Key::A);
+        assert_eq!(input.get_time(), 0.5);
+        engine.step(0.25);
+        assert_eq!(engine.context().get_input(id).unwrap().get_events()[0].time, 0.5);
+    }
+
+    #[test]
+    fn engines_keep_their_own_log_level() {
+        let mut first = Engine::headless();
+        let second = Engine::headless();
+        first.context_mut().logger_mut().set_level(LogLevel::Error);
+
+        assert_eq!(first.context().logger().get_level(), LogLevel::Error);
+        assert_eq!(second.context().logger().get_level(), LogLevel::Info);
+    }
+
+}
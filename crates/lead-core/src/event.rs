@@ -0,0 +1,332 @@
+use lead_logger::warn;
+use std::any::{Any, TypeId};
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, VecDeque};
+use std::marker::PhantomData;
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+//                                          Event Reader                                          //
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+// Cursor into a channel, each subscriber keeps its own so they all see every event once
+pub struct EventReader<E> {
+    cursor: u64,
+    _marker: PhantomData<fn() -> E>,
+}
+
+impl<E> EventReader<E> {
+
+    fn new(cursor: u64) -> EventReader<E> {
+        return EventReader {
+            cursor,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn get_cursor(&self) -> u64 {
+        return self.cursor;
+    }
+
+}
+
+impl<E> Clone for EventReader<E> {
+
+    fn clone(&self) -> Self {
+        return EventReader::new(self.cursor);
+    }
+
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+//                                         Event Channel                                          //
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+// Events stay readable during the frame they were published in and the next one, so a reader
+// updated before the publisher within a frame still gets them
+pub struct EventChannel<E> {
+    events: VecDeque<E>,
+    // Sequence number of the first stored event
+    start: u64,
+    // Number of stored events published before the current frame
+    previous: usize,
+}
+
+impl<E> EventChannel<E> {
+
+    pub fn new() -> EventChannel<E> {
+        return EventChannel {
+            events: VecDeque::new(),
+            start: 0,
+            previous: 0,
+        }
+    }
+
+    pub fn publish(&mut self, event: E) {
+        self.events.push_back(event);
+    }
+
+    // Reader which only sees the events published after its creation
+    pub fn reader(&self) -> EventReader<E> {
+        return EventReader::new(self.end());
+    }
+
+    // Reader which also sees the events still stored in the channel
+    pub fn reader_from_start(&self) -> EventReader<E> {
+        return EventReader::new(self.start);
+    }
+
+    // Events the reader has not seen yet, in publishing order
+    pub fn read<'a>(&'a self, reader: &mut EventReader<E>) -> impl Iterator<Item = &'a E> {
+        if reader.cursor < self.start {
+            warn!("Events", "Reader missed {} events of '{}'", self.start - reader.cursor, std::any::type_name::<E>());
+        }
+
+        let skip = reader.cursor.clamp(self.start, self.end()) - self.start;
+        reader.cursor = self.end();
+        return self.events.iter().skip(skip as usize);
+    }
+
+    pub fn has_unread(&self, reader: &EventReader<E>) -> bool {
+        return reader.cursor < self.end();
+    }
+
+    // Drops the events of the previous frame, called once per frame
+    pub fn update(&mut self) {
+        self.events.drain(..self.previous);
+        self.start += self.previous as u64;
+        self.previous = self.events.len();
+    }
+
+    pub fn clear(&mut self) {
+        self.start = self.end();
+        self.events.clear();
+        self.previous = 0;
+    }
+
+    fn end(&self) -> u64 {
+        return self.start + self.events.len() as u64;
+    }
+
+    /* ====================================== Getters ======================================= */
+
+    pub fn len(&self) -> usize {
+        return self.events.len();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        return self.events.is_empty();
+    }
+
+    // Every stored event, regardless of readers
+    pub fn iter(&self) -> impl Iterator<Item = &E> {
+        return self.events.iter();
+    }
+
+}
+
+impl<E> Default for EventChannel<E> {
+
+    fn default() -> Self {
+        return EventChannel::new();
+    }
+
+}
+
+trait AnyChannel: Any {
+
+    fn update(&mut self);
+    fn clear(&mut self);
+    fn as_any(&self) -> &dyn Any;
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+
+}
+
+impl<E: 'static> AnyChannel for EventChannel<E> {
+
+    fn update(&mut self) {
+        EventChannel::update(self);
+    }
+
+    fn clear(&mut self) {
+        EventChannel::clear(self);
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        return self;
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        return self;
+    }
+
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+//                                           Event Bus                                            //
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+// One channel per event type, created on first use
+pub struct EventBus {
+    channels: HashMap<TypeId, Box<dyn AnyChannel>>,
+}
+
+impl EventBus {
+
+    pub fn new() -> EventBus {
+        return EventBus {
+            channels: HashMap::new(),
+        }
+    }
+
+    pub fn publish<E: 'static>(&mut self, event: E) {
+        self.channel_mut::<E>().publish(event);
+    }
+
+    pub fn reader<E: 'static>(&mut self) -> EventReader<E> {
+        return self.channel_mut::<E>().reader();
+    }
+
+    pub fn read<'a, E: 'static>(&'a self, reader: &mut EventReader<E>) -> impl Iterator<Item = &'a E> {
+        return self.channel::<E>().map(|channel| channel.read(reader)).into_iter().flatten();
+    }
+
+    pub fn has_unread<E: 'static>(&self, reader: &EventReader<E>) -> bool {
+        return self.channel::<E>().map(|channel| channel.has_unread(reader)).unwrap_or(false);
+    }
+
+    pub fn channel<E: 'static>(&self) -> Option<&EventChannel<E>> {
+        return self.channels.get(&TypeId::of::<E>())
+            .and_then(|channel| channel.as_any().downcast_ref::<EventChannel<E>>());
+    }
+
+    pub fn channel_mut<E: 'static>(&mut self) -> &mut EventChannel<E> {
+        let channel = match self.channels.entry(TypeId::of::<E>()) {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => entry.insert(Box::new(EventChannel::<E>::new())),
+        };
+        return channel.as_any_mut().downcast_mut::<EventChannel<E>>().unwrap();
+    }
+
+    // Advances every channel to the next frame
+    pub fn update(&mut self) {
+        for channel in self.channels.values_mut() {
+            channel.update();
+        }
+    }
+
+    pub fn clear(&mut self) {
+        for channel in self.channels.values_mut() {
+            channel.clear();
+        }
+    }
+
+}
+
+impl Default for EventBus {
+
+    fn default() -> Self {
+        return EventBus::new();
+    }
+
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+//                                             Tests                                              //
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn read_all(channel: &EventChannel<u32>, reader: &mut EventReader<u32>) -> Vec<u32> {
+        return channel.read(reader).copied().collect();
+    }
+
+    #[test]
+    fn events_expire_after_two_frames() {
+        let mut channel = EventChannel::new();
+        let mut early = channel.reader();
+        channel.publish(1);
+        assert_eq!(read_all(&channel, &mut early), [1]);
+
+        // A reader updated later in the frame or during the next one still gets the event
+        let mut late = channel.reader_from_start();
+        channel.update();
+        channel.publish(2);
+        assert_eq!(channel.len(), 2);
+        assert_eq!(read_all(&channel, &mut late), [1, 2]);
+
+        channel.update();
+        assert_eq!(channel.iter().copied().collect::<Vec<_>>(), [2]);
+        assert_eq!(read_all(&channel, &mut early), [2]);
+        channel.update();
+        assert!(channel.is_empty());
+    }
+
+    #[test]
+    fn readers_see_each_event_once() {
+        let mut channel = EventChannel::new();
+        channel.publish(1);
+
+        let mut reader = channel.reader();
+        assert!(!channel.has_unread(&reader));
+        channel.publish(2);
+        channel.publish(3);
+        assert!(channel.has_unread(&reader));
+        assert_eq!(read_all(&channel, &mut reader), [2, 3]);
+        assert!(read_all(&channel, &mut reader).is_empty());
+
+        let mut copy = reader.clone();
+        channel.publish(4);
+        assert_eq!(read_all(&channel, &mut reader), [4]);
+        assert_eq!(read_all(&channel, &mut copy), [4]);
+    }
+
+    #[test]
+    fn slow_readers_skip_expired_events() {
+        let mut channel = EventChannel::new();
+        let mut reader = channel.reader();
+        channel.publish(1);
+        channel.update();
+        channel.publish(2);
+        channel.update();
+        channel.publish(3);
+        channel.update();
+
+        assert_eq!(read_all(&channel, &mut reader), [3]);
+        assert_eq!(reader.get_cursor(), 3);
+    }
+
+    #[test]
+    fn cleared_channels_keep_counting() {
+        let mut channel = EventChannel::new();
+        let mut reader = channel.reader();
+        channel.publish(1);
+        channel.clear();
+        assert!(channel.is_empty());
+        assert!(!channel.has_unread(&channel.reader_from_start()));
+
+        channel.publish(2);
+        assert_eq!(read_all(&channel, &mut reader), [2]);
+    }
+
+    #[test]
+    fn bus_keeps_a_channel_per_type() {
+        let mut bus = EventBus::new();
+        let mut numbers = bus.reader::<u32>();
+        let mut names = bus.reader::<&str>();
+        assert!(bus.channel::<i64>().is_none());
+
+        bus.publish(1u32);
+        bus.publish("one");
+        assert!(bus.has_unread(&numbers));
+        assert_eq!(bus.read(&mut numbers).copied().collect::<Vec<_>>(), [1]);
+        assert_eq!(bus.read(&mut names).copied().collect::<Vec<_>>(), ["one"]);
+
+        bus.update();
+        bus.update();
+        assert!(bus.channel::<u32>().unwrap().is_empty());
+        assert!(bus.channel::<&str>().unwrap().is_empty());
+    }
+
+}
@@ -0,0 +1,178 @@
+use std::fmt::{Display, Formatter};
+use std::fs::File;
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+//                                          Image Error                                           //
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+#[derive(Debug)]
+pub enum ImageError {
+    Io {
+        path: PathBuf,
+        error: std::io::Error,
+    },
+    Decode {
+        path: PathBuf,
+        message: String,
+    },
+    // The pixel buffer does not hold width * height RGBA pixels
+    InvalidSize {
+        width: u32,
+        height: u32,
+        len: usize,
+    },
+}
+
+impl Display for ImageError {
+
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        return match self {
+            ImageError::Io { path, error } => write!(f, "Failed to read '{}': {}", path.display(), error),
+            ImageError::Decode { path, message } => write!(f, "Failed to decode '{}': {}", path.display(), message),
+            ImageError::InvalidSize { width, height, len } => write!(f, "Expected {} bytes for {}x{} RGBA pixels, found {}", *width as usize * *height as usize * 4, width, height, len),
+        }
+    }
+
+}
+
+impl std::error::Error for ImageError {}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+//                                             Image                                              //
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+// 8-bit RGBA image, rows from top to bottom
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct Image {
+    width: u32,
+    height: u32,
+    pixels: Vec<u8>,
+}
+
+impl Image {
+
+    pub fn new(width: u32, height: u32, pixels: Vec<u8>) -> Result<Image, ImageError> {
+        if pixels.len() != width as usize * height as usize * 4 {
+            return Err(ImageError::InvalidSize { width, height, len: pixels.len() });
+        }
+        return Ok(Image {
+            width,
+            height,
+            pixels,
+        });
+    }
+
+    // Only PNG files are supported, any color type and bit depth is converted to 8-bit RGBA
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Image, ImageError> {
+        let path = path.as_ref();
+        let file = File::open(path).map_err(|error| ImageError::Io { path: path.to_path_buf(), error })?;
+        let decode_error = |err: png::DecodingError| ImageError::Decode { path: path.to_path_buf(), message: err.to_string() };
+
+        let mut decoder = png::Decoder::new(BufReader::new(file));
+        decoder.set_transformations(png::Transformations::normalize_to_color8());
+        let mut reader = decoder.read_info().map_err(decode_error)?;
+        let mut buffer = vec![0; reader.output_buffer_size().unwrap_or(0)];
+        let info = reader.next_frame(&mut buffer).map_err(decode_error)?;
+        buffer.truncate(info.buffer_size());
+
+        let pixels = match info.color_type {
+            png::ColorType::Rgba => buffer,
+            png::ColorType::Rgb => buffer.chunks_exact(3).flat_map(|p| [p[0], p[1], p[2], 255]).collect(),
+            png::ColorType::GrayscaleAlpha => buffer.chunks_exact(2).flat_map(|p| [p[0], p[0], p[0], p[1]]).collect(),
+            png::ColorType::Grayscale => buffer.iter().flat_map(|&p| [p, p, p, 255]).collect(),
+            png::ColorType::Indexed => {
+                return Err(ImageError::Decode { path: path.to_path_buf(), message: "indexed colors were not expanded".to_string() });
+            }
+        };
+
+        return Image::new(info.width, info.height, pixels)
+            .map_err(|err| ImageError::Decode { path: path.to_path_buf(), message: err.to_string() });
+    }
+
+    pub(crate) fn to_pixel_image(&self) -> glfw::PixelImage {
+        return glfw::PixelImage {
+            width: self.width,
+            height: self.height,
+            pixels: self.pixels.chunks_exact(4).map(|p| u32::from_ne_bytes([p[0], p[1], p[2], p[3]])).collect(),
+        }
+    }
+
+    /* ====================================== Getters ======================================= */
+
+    pub fn get_width(&self) -> u32 {
+        return self.width;
+    }
+
+    pub fn get_height(&self) -> u32 {
+        return self.height;
+    }
+
+    pub fn get_pixels(&self) -> &[u8] {
+        return &self.pixels;
+    }
+
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+//                                             Tests                                              //
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_png(name: &str, width: u32, height: u32, color_type: png::ColorType, data: &[u8]) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("lead-image-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(name);
+
+        let mut encoder = png::Encoder::new(File::create(&path).unwrap(), width, height);
+        encoder.set_color(color_type);
+        encoder.set_depth(png::BitDepth::Eight);
+        encoder.write_header().unwrap().write_image_data(data).unwrap();
+        return path;
+    }
+
+    #[test]
+    fn pixels_have_to_match_the_size() {
+        let image = Image::new(2, 1, vec![255, 0, 0, 255, 0, 255, 0, 128]).unwrap();
+        assert_eq!(image.get_width(), 2);
+        assert_eq!(image.get_height(), 1);
+
+        match Image::new(2, 2, vec![0; 8]) {
+            Err(err @ ImageError::InvalidSize { width: 2, height: 2, len: 8 }) => {
+                assert_eq!(err.to_string(), "Expected 16 bytes for 2x2 RGBA pixels, found 8");
+            }
+            result => panic!("Expected a size error, got {:?}", result),
+        }
+    }
+
+    #[test]
+    fn png_files_are_decoded_to_rgba() {
+        let rgba = [1, 2, 3, 4, 5, 6, 7, 8];
+        let path = write_png("rgba.png", 2, 1, png::ColorType::Rgba, &rgba);
+        assert_eq!(Image::load(path).unwrap(), Image::new(2, 1, rgba.to_vec()).unwrap());
+
+        let path = write_png("rgb.png", 1, 2, png::ColorType::Rgb, &[10, 20, 30, 40, 50, 60]);
+        assert_eq!(Image::load(path).unwrap().get_pixels(), [10, 20, 30, 255, 40, 50, 60, 255]);
+
+        let path = write_png("gray.png", 2, 1, png::ColorType::Grayscale, &[0, 200]);
+        assert_eq!(Image::load(path).unwrap().get_pixels(), [0, 0, 0, 255, 200, 200, 200, 255]);
+
+        let path = write_png("gray-alpha.png", 1, 1, png::ColorType::GrayscaleAlpha, &[90, 30]);
+        assert_eq!(Image::load(path).unwrap().get_pixels(), [90, 90, 90, 30]);
+    }
+
+    #[test]
+    fn unreadable_files_are_reported() {
+        let dir = std::env::temp_dir().join(format!("lead-image-{}", std::process::id()));
+        assert!(matches!(Image::load(dir.join("missing.png")), Err(ImageError::Io { .. })));
+
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("text.png"), "not a png").unwrap();
+        assert!(matches!(Image::load(dir.join("text.png")), Err(ImageError::Decode { .. })));
+    }
+
+}
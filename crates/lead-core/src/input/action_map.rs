@@ -0,0 +1,491 @@
+use crate::config::{ConfigError, ConfigFile, ConfigSection, ValidationError};
+use crate::input::gamepad::{Gamepad, GamepadAxis, GamepadButton};
+use crate::input::keys::*;
+use crate::input::{Action, Input};
+use glfw::{Key, MouseButton};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+use std::path::Path;
+use std::str::FromStr;
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+//                                          Input Source                                          //
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum InputSource {
+    Key(Key),
+    MouseButton(MouseButton),
+    ScrollX,
+    ScrollY,
+    GamepadButton(GamepadButton),
+    GamepadAxis(GamepadAxis),
+}
+
+impl InputSource {
+
+    // 1.0 while a key or button is held, the scroll delta for scroll axes, gamepads are merged by
+    // keeping the value furthest from 0
+    pub fn get_value(&self, input: &Input) -> f32 {
+        let held = |action: Action| if action.is_pressed_or_down() { 1.0 } else { 0.0 };
+        let gamepads = |value: &dyn Fn(&Gamepad) -> f32| -> f32 {
+            return input.gamepads.iter()
+                .filter(|gamepad| gamepad.is_mapped())
+                .map(value)
+                .fold(0.0, |result, value| if value.abs() > result.abs() { value } else { result });
+        };
+
+        return match self {
+            InputSource::Key(key) => held(input.keyboard.get_key(*key)),
+            InputSource::MouseButton(button) => held(input.mouse.get_button(*button)),
+            InputSource::ScrollX => input.mouse.get_scroll_x() as f32,
+            InputSource::ScrollY => input.mouse.get_scroll_y() as f32,
+            InputSource::GamepadButton(button) => gamepads(&|gamepad| held(gamepad.get_button(*button))),
+            InputSource::GamepadAxis(axis) => gamepads(&|gamepad| gamepad.get_axis(*axis)),
+        }
+    }
+
+    // Scrolling and gamepad axes, which can report negative values
+    pub fn is_axis(&self) -> bool {
+        return matches!(self, InputSource::ScrollX | InputSource::ScrollY | InputSource::GamepadAxis(_));
+    }
+
+    // First key or mouse button pressed this frame, used to let players pick a new binding
+    pub fn get_pressed(input: &Input) -> Option<InputSource> {
+        if let Some(key) = KEYS.iter().find(|key| input.keyboard.get_key(**key).is_pressed()) {
+            return Some(InputSource::Key(*key));
+        }
+
+        let button = (0..=glfw::ffi::MOUSE_BUTTON_LAST)
+            .filter_map(MouseButton::from_i32)
+            .find(|button| input.mouse.get_button(*button).is_pressed());
+        if let Some(button) = button {
+            return Some(InputSource::MouseButton(button));
+        }
+
+        return input.gamepads.iter()
+            .flat_map(|gamepad| (0..=glfw::ffi::GAMEPAD_BUTTON_LAST).filter_map(GamepadButton::from_i32).map(move |button| (gamepad, button)))
+            .find(|(gamepad, button)| gamepad.get_button(*button).is_pressed())
+            .map(|(_, button)| InputSource::GamepadButton(button));
+    }
+
+}
+
+impl Display for InputSource {
+
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        return match self {
+            InputSource::Key(key) => write!(f, "key:{}", key_name(*key)),
+            InputSource::MouseButton(button) => write!(f, "mouse:{}", mouse_button_name(*button)),
+            InputSource::ScrollX => write!(f, "scroll:x"),
+            InputSource::ScrollY => write!(f, "scroll:y"),
+            InputSource::GamepadButton(button) => write!(f, "gamepad:{}", gamepad_button_name(*button)),
+            InputSource::GamepadAxis(axis) => write!(f, "axis:{}", gamepad_axis_name(*axis)),
+        }
+    }
+
+}
+
+impl FromStr for InputSource {
+
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let (kind, name) = value.split_once(':')
+            .ok_or_else(|| format!("expected \"<kind>:<name>\" with a kind of key, mouse, scroll, gamepad or axis, found \"{}\"", value))?;
+
+        let source = match kind.trim() {
+            "key" => key_from_name(name.trim()).map(InputSource::Key),
+            "mouse" => mouse_button_from_name(name.trim()).map(InputSource::MouseButton),
+            "scroll" => match name.trim() {
+                "x" => Some(InputSource::ScrollX),
+                "y" => Some(InputSource::ScrollY),
+                _ => None,
+            },
+            "gamepad" => gamepad_button_from_name(name.trim()).map(InputSource::GamepadButton),
+            "axis" => gamepad_axis_from_name(name.trim()).map(InputSource::GamepadAxis),
+            _ => return Err(format!("unknown input kind '{}' in \"{}\"", kind, value)),
+        };
+        return source.ok_or_else(|| format!("unknown {} '{}'", kind, name));
+    }
+
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+//                                            Binding                                             //
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+// Written as "key:space" or "-key:a" in bindings files, the minus sign inverts the value so two
+// keys can drive both directions of an axis
+#[derive(Copy, Clone, PartialEq, Debug, Deserialize, Serialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Binding {
+    pub source: InputSource,
+    pub inverted: bool,
+}
+
+impl Binding {
+
+    pub fn new(source: InputSource) -> Binding {
+        return Binding {
+            source,
+            inverted: false,
+        }
+    }
+
+    pub fn inverted(source: InputSource) -> Binding {
+        return Binding {
+            source,
+            inverted: true,
+        }
+    }
+
+    pub fn key(key: Key) -> Binding {
+        return Binding::new(InputSource::Key(key));
+    }
+
+    pub fn mouse_button(button: MouseButton) -> Binding {
+        return Binding::new(InputSource::MouseButton(button));
+    }
+
+    pub fn get_value(&self, input: &Input) -> f32 {
+        let value = self.source.get_value(input);
+        return if self.inverted { -value } else { value };
+    }
+
+}
+
+impl From<InputSource> for Binding {
+
+    fn from(source: InputSource) -> Self {
+        return Binding::new(source);
+    }
+
+}
+
+impl Display for Binding {
+
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if self.inverted {
+            write!(f, "-")?;
+        }
+        return write!(f, "{}", self.source);
+    }
+
+}
+
+impl FromStr for Binding {
+
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let value = value.trim();
+        return match value.strip_prefix('-') {
+            Some(source) => Ok(Binding::inverted(source.parse()?)),
+            None => Ok(Binding::new(value.parse()?)),
+        }
+    }
+
+}
+
+impl TryFrom<String> for Binding {
+
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        return value.parse();
+    }
+
+}
+
+impl From<Binding> for String {
+
+    fn from(binding: Binding) -> Self {
+        return binding.to_string();
+    }
+
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+//                                         Bindings File                                          //
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+// The [actions] table of a bindings file, mapping action names to their bindings
+#[derive(Clone, Default, Debug, Deserialize, Serialize)]
+#[serde(transparent)]
+pub struct BindingsConfig(pub BTreeMap<String, Vec<Binding>>);
+
+impl ConfigSection for BindingsConfig {
+
+    const NAME: &'static str = "actions";
+
+    fn validate(&self) -> Result<(), Vec<ValidationError>> {
+        let errors: Vec<ValidationError> = self.0.keys()
+            .filter(|name| name.trim().is_empty())
+            .map(|name| ValidationError::new(name, "action names can not be empty"))
+            .collect();
+
+        if errors.is_empty() {
+            return Ok(());
+        }
+        return Err(errors);
+    }
+
+}
+
+#[derive(Serialize)]
+struct BindingsFile<'a> {
+    actions: &'a BindingsConfig,
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+//                                           Action Map                                           //
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+// Values above this are considered held when an action is queried as a button
+pub const PRESS_THRESHOLD: f32 = 0.5;
+
+#[derive(Clone, Default, Debug)]
+struct ActionState {
+    bindings: Vec<Binding>,
+    value: f32,
+    action: Action,
+    // Inputs blocked for this input context are ignored
+    context: Option<String>,
+}
+
+// Named actions bound to physical inputs, unknown actions are reported as Up with a value of 0
+pub struct ActionMap {
+    actions: BTreeMap<String, ActionState>,
+}
+
+impl ActionMap {
+
+    pub fn new() -> ActionMap {
+        return ActionMap {
+            actions: BTreeMap::new(),
+        }
+    }
+
+    // Called once per frame after the input was updated
+    pub fn update(&mut self, input: &Input) {
+        for state in self.actions.values_mut() {
+            let value: f32 = state.bindings.iter()
+                .filter(|binding| match &state.context {
+                    Some(context) => !input.contexts.is_blocked(context, &binding.source),
+                    None => true,
+                })
+                .map(|binding| binding.get_value(input))
+                .sum();
+            state.value = value.clamp(-1.0, 1.0);
+
+            let held = state.value.abs() > PRESS_THRESHOLD;
+            state.action = match (state.action.is_pressed_or_down(), held) {
+                (false, true) => Action::Pressed,
+                (true, true) => Action::Down,
+                (true, false) => Action::Released,
+                (false, false) => Action::Up,
+            };
+        }
+    }
+
+    /* ====================================== Bindings ====================================== */
+
+    pub fn bind<B: Into<Binding>>(&mut self, action: &str, binding: B) {
+        let binding = binding.into();
+        let state = self.actions.entry(action.to_string()).or_default();
+        if !state.bindings.contains(&binding) {
+            state.bindings.push(binding);
+        }
+    }
+
+    pub fn unbind<B: Into<Binding>>(&mut self, action: &str, binding: B) {
+        let binding = binding.into();
+        if let Some(state) = self.actions.get_mut(action) {
+            state.bindings.retain(|bound| *bound != binding);
+        }
+    }
+
+    // Replaces one binding of an action, keeping its position
+    pub fn rebind<B: Into<Binding>>(&mut self, action: &str, old: B, new: B) {
+        let (old, new) = (old.into(), new.into());
+        match self.actions.get_mut(action).and_then(|state| state.bindings.iter_mut().find(|bound| **bound == old)) {
+            Some(bound) => *bound = new,
+            None => self.bind(action, new),
+        }
+    }
+
+    pub fn set_bindings(&mut self, action: &str, bindings: Vec<Binding>) {
+        self.actions.entry(action.to_string()).or_default().bindings = bindings;
+    }
+
+    pub fn clear_bindings(&mut self, action: &str) {
+        self.set_bindings(action, Vec::new());
+    }
+
+    pub fn remove_action(&mut self, action: &str) -> bool {
+        return self.actions.remove(action).is_some();
+    }
+
+    // The action only sees the inputs which are not blocked for the context
+    pub fn set_context(&mut self, action: &str, context: Option<&str>) {
+        self.actions.entry(action.to_string()).or_default().context = context.map(str::to_string);
+    }
+
+    pub fn get_context(&self, action: &str) -> Option<&str> {
+        return self.actions.get(action).and_then(|state| state.context.as_deref());
+    }
+
+    pub fn get_bindings(&self, action: &str) -> &[Binding] {
+        return self.actions.get(action).map(|state| state.bindings.as_slice()).unwrap_or(&[]);
+    }
+
+    pub fn get_action_names(&self) -> Vec<&str> {
+        return self.actions.keys().map(String::as_str).collect();
+    }
+
+    // Replaces the bindings of every action listed in the file, other actions are left untouched
+    pub fn load_bindings<P: AsRef<Path>>(&mut self, path: P) -> Result<(), ConfigError> {
+        if let Some(config) = ConfigFile::load(path)?.section::<BindingsConfig>()? {
+            self.apply_bindings(config);
+        }
+        return Ok(());
+    }
+
+    pub fn apply_bindings(&mut self, config: BindingsConfig) {
+        for (action, bindings) in config.0 {
+            self.set_bindings(&action, bindings);
+        }
+    }
+
+    pub fn get_bindings_config(&self) -> BindingsConfig {
+        return BindingsConfig(self.actions.iter().map(|(name, state)| (name.clone(), state.bindings.clone())).collect());
+    }
+
+    pub fn save_bindings<P: AsRef<Path>>(&self, path: P) -> Result<(), ConfigError> {
+        let path = path.as_ref();
+        let io_error = |error: std::io::Error| ConfigError::Io { path: path.to_path_buf(), error };
+
+        let config = self.get_bindings_config();
+        let source = toml::to_string(&BindingsFile { actions: &config })
+            .map_err(|err| io_error(std::io::Error::new(std::io::ErrorKind::InvalidData, err.to_string())))?;
+        if let Some(parent) = path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent).map_err(io_error)?;
+        }
+        return std::fs::write(path, source).map_err(io_error);
+    }
+
+    /* ====================================== Getters ======================================= */
+
+    pub fn get_action(&self, action: &str) -> Action {
+        return self.actions.get(action).map(|state| state.action).unwrap_or_default();
+    }
+
+    // Sum of the bound values clamped to [-1, 1]
+    pub fn get_value(&self, action: &str) -> f32 {
+        return self.actions.get(action).map(|state| state.value).unwrap_or(0.0);
+    }
+
+    pub fn is_pressed(&self, action: &str) -> bool {
+        return self.get_action(action).is_pressed();
+    }
+
+    pub fn is_down(&self, action: &str) -> bool {
+        return self.get_action(action).is_pressed_or_down();
+    }
+
+    pub fn is_released(&self, action: &str) -> bool {
+        return self.get_action(action).is_released();
+    }
+
+}
+
+impl Default for ActionMap {
+
+    fn default() -> Self {
+        return ActionMap::new();
+    }
+
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+//                                             Tests                                              //
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::{InputEvent, Modifiers};
+
+    fn key(key: Key, action: Action) -> InputEvent {
+        return InputEvent::Key(key, -1, action, Modifiers::empty());
+    }
+
+    #[test]
+    fn bindings_parse_every_kind_of_source() {
+        assert_eq!("key:space".parse(), Ok(Binding::key(Key::Space)));
+        assert_eq!("key:left-shift".parse(), Ok(Binding::key(Key::LeftShift)));
+        assert_eq!("mouse:right".parse(), Ok(Binding::mouse_button(MouseButton::Button2)));
+        assert_eq!("scroll:y".parse(), Ok(Binding::new(InputSource::ScrollY)));
+        assert_eq!("gamepad:dpad-up".parse(), Ok(Binding::new(InputSource::GamepadButton(GamepadButton::ButtonDpadUp))));
+        assert_eq!(" -axis:left-x ".parse(), Ok(Binding::inverted(InputSource::GamepadAxis(GamepadAxis::AxisLeftX))));
+        assert_eq!("key : A".parse(), Ok(Binding::key(Key::A)));
+    }
+
+    #[test]
+    fn bindings_round_trip_through_their_names() {
+        let bindings = [
+            Binding::key(Key::KpAdd),
+            Binding::inverted(InputSource::Key(Key::A)),
+            Binding::mouse_button(MouseButton::Button5),
+            Binding::inverted(InputSource::ScrollX),
+            Binding::new(InputSource::GamepadButton(GamepadButton::ButtonLeftBumper)),
+            Binding::new(InputSource::GamepadAxis(GamepadAxis::AxisRightTrigger)),
+        ];
+        for binding in bindings {
+            assert_eq!(binding.to_string().parse(), Ok(binding));
+        }
+        assert_eq!(Binding::inverted(InputSource::Key(Key::A)).to_string(), "-key:a");
+    }
+
+    #[test]
+    fn invalid_bindings_are_explained() {
+        assert_eq!("space".parse::<Binding>(), Err("expected \"<kind>:<name>\" with a kind of key, mouse, scroll, gamepad or axis, found \"space\"".to_string()));
+        assert_eq!("pad:a".parse::<Binding>(), Err("unknown input kind 'pad' in \"pad:a\"".to_string()));
+        assert_eq!("key:enterr".parse::<Binding>(), Err("unknown key 'enterr'".to_string()));
+        assert_eq!("scroll:z".parse::<Binding>(), Err("unknown scroll 'z'".to_string()));
+    }
+
+    #[test]
+    fn bindings_files_report_the_invalid_binding() {
+        let file = ConfigFile::from_source("bindings.toml", "[actions]\njump = [\"key:space\", \"gamepad:a\"]\nfire = [\"mouse:lefty\"]\n");
+        let error = file.section::<BindingsConfig>().unwrap_err();
+        assert!(error.to_string().contains("unknown mouse 'lefty' for key `actions.fire`"));
+
+        let file = ConfigFile::from_source("bindings.toml", "[actions]\njump = [\"key:space\", \"gamepad:a\"]\n");
+        let mut actions = ActionMap::new();
+        actions.apply_bindings(file.section::<BindingsConfig>().unwrap().unwrap());
+        assert_eq!(actions.get_bindings("jump"), [Binding::key(Key::Space), Binding::new(InputSource::GamepadButton(GamepadButton::ButtonA))]);
+    }
+
+    #[test]
+    fn inverted_bindings_drive_both_directions_of_an_axis() {
+        let mut input = Input::new();
+        let mut actions = ActionMap::new();
+        actions.bind("move", Binding::key(Key::D));
+        actions.bind("move", Binding::inverted(InputSource::Key(Key::A)));
+
+        input.handle_event(0.0, key(Key::A, Action::Pressed));
+        actions.update(&input);
+        assert_eq!(actions.get_value("move"), -1.0);
+        assert!(actions.is_pressed("move"));
+
+        input.update();
+        input.handle_event(0.0, key(Key::D, Action::Pressed));
+        actions.update(&input);
+        assert_eq!(actions.get_value("move"), 0.0);
+        assert!(actions.is_released("move"));
+    }
+
+}
@@ -0,0 +1,569 @@
+use crate::config::{ConfigError, ConfigFile, ConfigSection, ValidationError};
+use crate::input::action_map::{Binding, InputSource, PRESS_THRESHOLD};
+use crate::input::gamepad::GamepadEvent;
+use crate::input::{Input, InputEvent, Key, Modifiers};
+use lead_logger::warn;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+//                                             Combo                                              //
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+// Times are in seconds. An input is held while its value is above PRESS_THRESHOLD, so an inverted
+// axis like "-axis:left-y" is held while pushed the other way. Inverted keys and buttons would never
+// be held and are rejected. In bindings files:
+// [combos.save]
+// type = "chord"
+// inputs = ["key:left-control", "key:left-shift", "key:s"]
+#[derive(Clone, PartialEq, Debug, Deserialize, Serialize)]
+#[serde(tag = "type", rename_all = "kebab-case")]
+pub enum Combo {
+    // Every input held at once, completes when the last one is pressed after the others. Modifier
+    // keys which are not part of the chord must not be held
+    Chord {
+        inputs: Vec<Binding>,
+    },
+    // Inputs pressed in order, each one within the window of the previous one
+    Sequence {
+        inputs: Vec<Binding>,
+        window: f64,
+    },
+    DoubleTap {
+        input: Binding,
+        window: f64,
+    },
+    // Completes once the input was held for the duration, then not again until it is released
+    Hold {
+        input: Binding,
+        duration: f64,
+    },
+}
+
+impl Combo {
+
+    pub fn chord<B: Into<Binding>>(inputs: Vec<B>) -> Combo {
+        return Combo::Chord { inputs: inputs.into_iter().map(Into::into).collect() }
+    }
+
+    pub fn sequence<B: Into<Binding>>(inputs: Vec<B>, window: f64) -> Combo {
+        return Combo::Sequence { inputs: inputs.into_iter().map(Into::into).collect(), window }
+    }
+
+    pub fn double_tap<B: Into<Binding>>(input: B, window: f64) -> Combo {
+        return Combo::DoubleTap { input: input.into(), window }
+    }
+
+    pub fn hold<B: Into<Binding>>(input: B, duration: f64) -> Combo {
+        return Combo::Hold { input: input.into(), duration }
+    }
+
+    // Every distinct input of the combo, in order
+    fn get_inputs(&self) -> Vec<Binding> {
+        let mut inputs: Vec<Binding> = Vec::new();
+        let all = match self {
+            Combo::Chord { inputs } | Combo::Sequence { inputs, .. } => inputs.clone(),
+            Combo::DoubleTap { input, .. } | Combo::Hold { input, .. } => vec![*input],
+        };
+        for input in all {
+            if !inputs.contains(&input) {
+                inputs.push(input);
+            }
+        }
+        return inputs;
+    }
+
+    fn validate(&self, name: &str) -> Option<ValidationError> {
+        if let Some(binding) = self.get_inputs().iter().find(|binding| binding.inverted && !binding.source.is_axis()) {
+            return Some(ValidationError::new(name, format!("only axes can be inverted, '{}' would never be held", binding)));
+        }
+
+        return match self {
+            Combo::Chord { inputs } | Combo::Sequence { inputs, .. } if inputs.is_empty() => {
+                Some(ValidationError::new(name, "combos need at least one input"))
+            }
+            Combo::Sequence { window, .. } | Combo::DoubleTap { window, .. } if *window <= 0.0 => {
+                Some(ValidationError::new(name, format!("window must be positive, found {}", window)))
+            }
+            Combo::Hold { duration, .. } if *duration < 0.0 => {
+                Some(ValidationError::new(name, format!("duration can not be negative, found {}", duration)))
+            }
+            _ => None,
+        }
+    }
+
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+//                                          Combo Event                                           //
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+// Published on the event bus with the name of the completed combo
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct ComboEvent(pub String);
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+//                                          Combos File                                           //
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+// The [combos] table of a bindings file, mapping combo names to their definition
+#[derive(Clone, Default, Debug, Deserialize, Serialize)]
+#[serde(transparent)]
+pub struct CombosConfig(pub BTreeMap<String, Combo>);
+
+impl ConfigSection for CombosConfig {
+
+    const NAME: &'static str = "combos";
+
+    fn validate(&self) -> Result<(), Vec<ValidationError>> {
+        let errors: Vec<ValidationError> = self.0.iter()
+            .filter_map(|(name, combo)| match name.trim().is_empty() {
+                true => Some(ValidationError::new(name, "combo names can not be empty")),
+                false => combo.validate(name),
+            })
+            .collect();
+
+        if errors.is_empty() {
+            return Ok(());
+        }
+        return Err(errors);
+    }
+
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+//                                         Combo Detector                                         //
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+#[derive(Clone, Debug)]
+struct ComboState {
+    combo: Combo,
+    inputs: Vec<Binding>,
+    // Whether each input was held last frame, to detect presses
+    held: Vec<bool>,
+    progress: usize,
+    timer: f64,
+    // A hold only completes once per press
+    fired: bool,
+    // Inputs blocked for this input context are never held
+    context: Option<String>,
+}
+
+impl ComboState {
+
+    fn new(combo: Combo) -> ComboState {
+        let inputs = combo.get_inputs();
+        return ComboState {
+            held: vec![false; inputs.len()],
+            combo,
+            inputs,
+            progress: 0,
+            timer: 0.0,
+            fired: false,
+            context: None,
+        }
+    }
+
+    fn update(&mut self, input: &Input, delta: f64) -> bool {
+        let blocked = |binding: &Binding| match &self.context {
+            Some(context) => input.contexts.is_blocked(context, &binding.source),
+            None => false,
+        };
+
+        // Presses are taken from the events of the frame so they keep their order, and the held
+        // state is followed along to know what was held when each input was pressed
+        let mut held = self.held.clone();
+        let mut pressed: Vec<Binding> = Vec::new();
+        let mut chord_completed = false;
+        for event in input.get_events() {
+            let (source, down, modifiers) = match &event.event {
+                InputEvent::Key(key, _, action, modifiers) => (InputSource::Key(*key), action.is_pressed_or_down(), *modifiers),
+                InputEvent::MouseButton(button, action) => (InputSource::MouseButton(*button), action.is_pressed_or_down(), input.keyboard.get_modifiers()),
+                InputEvent::Gamepad(GamepadEvent::Button(id, button, action)) if input.gamepads.get(*id).is_some_and(|gamepad| gamepad.is_mapped()) => {
+                    (InputSource::GamepadButton(*button), action.is_pressed_or_down(), input.keyboard.get_modifiers())
+                }
+                _ => continue,
+            };
+
+            for (i, binding) in self.inputs.iter().enumerate() {
+                if binding.source != source || blocked(binding) {
+                    continue;
+                }
+                if down && !held[i] {
+                    pressed.push(*binding);
+                    chord_completed |= self.completes_chord(i, &held, modifiers);
+                }
+                held[i] = down;
+            }
+        }
+
+        // Axes have no press events, they are pressed once their value goes above the threshold
+        let values: Vec<bool> = self.inputs.iter()
+            .map(|binding| !blocked(binding) && binding.get_value(input) > PRESS_THRESHOLD)
+            .collect();
+        for (i, binding) in self.inputs.iter().enumerate().filter(|(_, binding)| binding.source.is_axis()) {
+            if values[i] && !self.held[i] {
+                pressed.push(*binding);
+                held[i] = true;
+                chord_completed |= self.completes_chord(i, &held, input.keyboard.get_modifiers());
+            }
+        }
+        self.held = values;
+        self.timer += delta;
+
+        let completed = match &self.combo {
+            Combo::Chord { .. } => chord_completed,
+            Combo::Sequence { inputs, window } => self.advance(inputs.clone(), *window, &pressed),
+            Combo::DoubleTap { input, window } => self.advance(vec![*input, *input], *window, &pressed),
+            Combo::Hold { duration, .. } => {
+                if !self.held[0] || !pressed.is_empty() {
+                    self.timer = 0.0;
+                    self.fired = false;
+                }
+                let completed = self.held[0] && !self.fired && self.timer >= *duration;
+                self.fired |= completed;
+                completed
+            }
+        };
+        return completed;
+    }
+
+    // A chord completes when its last input is pressed while the others are already held, and no
+    // modifier key outside of the chord is held, so Ctrl+S does not fire on Ctrl+Shift+S
+    fn completes_chord(&self, pressed: usize, held: &[bool], modifiers: Modifiers) -> bool {
+        if !matches!(self.combo, Combo::Chord { .. }) || pressed != self.inputs.len() - 1 {
+            return false;
+        }
+
+        let chord_modifiers = self.inputs.iter()
+            .filter_map(|binding| match binding.source {
+                InputSource::Key(key) => modifier_of(key),
+                _ => None,
+            })
+            .fold(Modifiers::empty(), |result, modifier| result | modifier);
+        let extra = modifiers & (Modifiers::Shift | Modifiers::Control | Modifiers::Alt | Modifiers::Super) & !chord_modifiers;
+        return extra.is_empty() && held[..pressed].iter().all(|held| *held);
+    }
+
+    // Steps through a sequence, a wrong input or a step out of the window starts over
+    fn advance(&mut self, steps: Vec<Binding>, window: f64, pressed: &[Binding]) -> bool {
+        if self.progress > 0 && self.timer > window {
+            self.progress = 0;
+        }
+
+        for binding in pressed {
+            if steps[self.progress] == *binding {
+                self.progress += 1;
+            } else {
+                self.progress = if steps[0] == *binding { 1 } else { 0 };
+            }
+            self.timer = 0.0;
+
+            if self.progress == steps.len() {
+                self.progress = 0;
+                return true;
+            }
+        }
+        return false;
+    }
+
+}
+
+fn modifier_of(key: Key) -> Option<Modifiers> {
+    return match key {
+        Key::LeftShift | Key::RightShift => Some(Modifiers::Shift),
+        Key::LeftControl | Key::RightControl => Some(Modifiers::Control),
+        Key::LeftAlt | Key::RightAlt => Some(Modifiers::Alt),
+        Key::LeftSuper | Key::RightSuper => Some(Modifiers::Super),
+        _ => None,
+    }
+}
+
+// Named combos checked every frame, completed combos are reported for the frame they complete in
+pub struct ComboDetector {
+    combos: BTreeMap<String, ComboState>,
+    completed: Vec<String>,
+}
+
+impl ComboDetector {
+
+    pub fn new() -> ComboDetector {
+        return ComboDetector {
+            combos: BTreeMap::new(),
+            completed: Vec::new(),
+        }
+    }
+
+    // Called once per frame after the input was updated
+    pub fn update(&mut self, input: &Input, delta: f64) {
+        self.completed.clear();
+        for (name, state) in self.combos.iter_mut() {
+            if state.update(input, delta) {
+                self.completed.push(name.clone());
+            }
+        }
+    }
+
+    /* ======================================= Combos ======================================= */
+
+    // Replaces any combo with the same name, its progress starts over and its context is kept.
+    // Invalid combos are rejected
+    pub fn add(&mut self, name: &str, combo: Combo) {
+        if let Some(err) = combo.validate(name) {
+            warn!("Combos", "Failed to add combo '{}': {}", err.key, err.message);
+            return;
+        }
+
+        let mut state = ComboState::new(combo);
+        state.context = self.combos.remove(name).and_then(|previous| previous.context);
+        self.combos.insert(name.to_string(), state);
+    }
+
+    pub fn remove(&mut self, name: &str) -> bool {
+        return self.combos.remove(name).is_some();
+    }
+
+    pub fn clear(&mut self) {
+        self.combos.clear();
+        self.completed.clear();
+    }
+
+    // The combo only sees the inputs which are not blocked for the context
+    pub fn set_context(&mut self, name: &str, context: Option<&str>) {
+        if let Some(state) = self.combos.get_mut(name) {
+            state.context = context.map(str::to_string);
+        }
+    }
+
+    pub fn get_context(&self, name: &str) -> Option<&str> {
+        return self.combos.get(name).and_then(|state| state.context.as_deref());
+    }
+
+    pub fn get_combo(&self, name: &str) -> Option<&Combo> {
+        return self.combos.get(name).map(|state| &state.combo);
+    }
+
+    pub fn get_combo_names(&self) -> Vec<&str> {
+        return self.combos.keys().map(String::as_str).collect();
+    }
+
+    // Adds or replaces every combo listed in the file, other combos are left untouched
+    pub fn load_combos<P: AsRef<Path>>(&mut self, path: P) -> Result<(), ConfigError> {
+        if let Some(config) = ConfigFile::load(path)?.section::<CombosConfig>()? {
+            self.apply_combos(config);
+        }
+        return Ok(());
+    }
+
+    pub fn apply_combos(&mut self, config: CombosConfig) {
+        for (name, combo) in config.0 {
+            self.add(&name, combo);
+        }
+    }
+
+    pub fn get_combos_config(&self) -> CombosConfig {
+        return CombosConfig(self.combos.iter().map(|(name, state)| (name.clone(), state.combo.clone())).collect());
+    }
+
+    /* ====================================== Getters ======================================= */
+
+    pub fn is_completed(&self, name: &str) -> bool {
+        return self.completed.iter().any(|completed| completed == name);
+    }
+
+    // Names of the combos completed this frame
+    pub fn get_completed(&self) -> &[String] {
+        return &self.completed;
+    }
+
+    // Steps of a sequence or double tap already entered
+    pub fn get_progress(&self, name: &str) -> usize {
+        return self.combos.get(name).map(|state| state.progress).unwrap_or(0);
+    }
+
+}
+
+impl Default for ComboDetector {
+
+    fn default() -> Self {
+        return ComboDetector::new();
+    }
+
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+//                                             Tests                                              //
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::Action;
+
+    fn key(key: Key, action: Action) -> InputEvent {
+        return InputEvent::Key(key, -1, action, Modifiers::empty());
+    }
+
+    // Runs one frame with the given events, returns the combos completed during it
+    fn frame(input: &mut Input, detector: &mut ComboDetector, delta: f64, events: Vec<InputEvent>) -> Vec<String> {
+        input.update();
+        for event in events {
+            input.handle_event(0.0, event);
+        }
+        detector.update(input, delta);
+        return detector.get_completed().to_vec();
+    }
+
+    #[test]
+    fn chords_complete_when_the_last_input_is_pressed() {
+        let mut input = Input::new();
+        let mut detector = ComboDetector::new();
+        detector.add("save", Combo::chord(vec![Binding::key(Key::LeftControl), Binding::key(Key::S)]));
+
+        assert!(frame(&mut input, &mut detector, 0.1, vec![key(Key::LeftControl, Action::Pressed)]).is_empty());
+        assert_eq!(frame(&mut input, &mut detector, 0.1, vec![key(Key::S, Action::Pressed)]), ["save"]);
+        assert!(detector.is_completed("save"));
+
+        // Holding the chord does not complete it again, pressing the last input again does
+        assert!(frame(&mut input, &mut detector, 0.1, vec![]).is_empty());
+        frame(&mut input, &mut detector, 0.1, vec![key(Key::S, Action::Released)]);
+        assert_eq!(frame(&mut input, &mut detector, 0.1, vec![key(Key::S, Action::Pressed)]), ["save"]);
+    }
+
+    #[test]
+    fn chords_follow_the_order_of_the_events() {
+        let mut input = Input::new();
+        let mut detector = ComboDetector::new();
+        detector.add("save", Combo::chord(vec![Binding::key(Key::LeftControl), Binding::key(Key::S)]));
+
+        // The modifier has to be held first, even within a single frame
+        assert!(frame(&mut input, &mut detector, 0.1, vec![key(Key::S, Action::Pressed), key(Key::LeftControl, Action::Pressed)]).is_empty());
+        frame(&mut input, &mut detector, 0.1, vec![key(Key::S, Action::Released), key(Key::LeftControl, Action::Released)]);
+        assert_eq!(frame(&mut input, &mut detector, 0.1, vec![key(Key::LeftControl, Action::Pressed), key(Key::S, Action::Pressed)]), ["save"]);
+    }
+
+    #[test]
+    fn chords_reject_extra_modifiers() {
+        let mut input = Input::new();
+        let mut detector = ComboDetector::new();
+        detector.add("save", Combo::chord(vec![Binding::key(Key::LeftControl), Binding::key(Key::S)]));
+        detector.add("save-as", Combo::chord(vec![Binding::key(Key::LeftControl), Binding::key(Key::LeftShift), Binding::key(Key::S)]));
+
+        frame(&mut input, &mut detector, 0.1, vec![key(Key::LeftControl, Action::Pressed), key(Key::LeftShift, Action::Pressed)]);
+        let modifiers = Modifiers::Control | Modifiers::Shift;
+        assert_eq!(frame(&mut input, &mut detector, 0.1, vec![InputEvent::Key(Key::S, -1, Action::Pressed, modifiers)]), ["save-as"]);
+
+        // Lock modifiers are ignored
+        frame(&mut input, &mut detector, 0.1, vec![key(Key::S, Action::Released), key(Key::LeftShift, Action::Released)]);
+        let modifiers = Modifiers::Control | Modifiers::CapsLock;
+        assert_eq!(frame(&mut input, &mut detector, 0.1, vec![InputEvent::Key(Key::S, -1, Action::Pressed, modifiers)]), ["save"]);
+    }
+
+    #[test]
+    fn taps_within_a_frame_advance_sequences() {
+        let mut input = Input::new();
+        let mut detector = ComboDetector::new();
+        detector.add("code", Combo::sequence(vec![Binding::key(Key::A), Binding::key(Key::B)], 1.0));
+
+        let events = vec![key(Key::B, Action::Pressed), key(Key::B, Action::Released), key(Key::A, Action::Pressed), key(Key::A, Action::Released), key(Key::B, Action::Pressed)];
+        assert_eq!(frame(&mut input, &mut detector, 0.1, events), ["code"]);
+    }
+
+    #[test]
+    fn sequences_have_to_follow_within_the_window() {
+        let mut input = Input::new();
+        let mut detector = ComboDetector::new();
+        detector.add("dash", Combo::sequence(vec![Binding::key(Key::Down), Binding::key(Key::Right)], 0.3));
+
+        frame(&mut input, &mut detector, 0.1, vec![key(Key::Down, Action::Pressed)]);
+        assert_eq!(detector.get_progress("dash"), 1);
+        assert_eq!(frame(&mut input, &mut detector, 0.2, vec![key(Key::Down, Action::Released), key(Key::Right, Action::Pressed)]), ["dash"]);
+        assert_eq!(detector.get_progress("dash"), 0);
+
+        frame(&mut input, &mut detector, 0.1, vec![key(Key::Right, Action::Released), key(Key::Down, Action::Pressed)]);
+        frame(&mut input, &mut detector, 0.2, vec![key(Key::Down, Action::Released)]);
+        assert!(frame(&mut input, &mut detector, 0.2, vec![key(Key::Right, Action::Pressed)]).is_empty());
+        assert_eq!(detector.get_progress("dash"), 0);
+    }
+
+    #[test]
+    fn wrong_inputs_restart_sequences() {
+        let mut input = Input::new();
+        let mut detector = ComboDetector::new();
+        detector.add("code", Combo::sequence(vec![Binding::key(Key::A), Binding::key(Key::B)], 1.0));
+
+        frame(&mut input, &mut detector, 0.1, vec![key(Key::B, Action::Pressed)]);
+        assert_eq!(detector.get_progress("code"), 0);
+        frame(&mut input, &mut detector, 0.1, vec![key(Key::B, Action::Released), key(Key::A, Action::Pressed)]);
+        frame(&mut input, &mut detector, 0.1, vec![key(Key::A, Action::Released)]);
+        frame(&mut input, &mut detector, 0.1, vec![key(Key::A, Action::Pressed)]);
+        assert_eq!(detector.get_progress("code"), 1);
+        assert_eq!(frame(&mut input, &mut detector, 0.1, vec![key(Key::B, Action::Pressed)]), ["code"]);
+    }
+
+    #[test]
+    fn double_taps_need_two_presses() {
+        let mut input = Input::new();
+        let mut detector = ComboDetector::new();
+        detector.add("roll", Combo::double_tap(Binding::key(Key::Space), 0.25));
+
+        assert!(frame(&mut input, &mut detector, 0.1, vec![key(Key::Space, Action::Pressed)]).is_empty());
+        assert!(frame(&mut input, &mut detector, 0.1, vec![key(Key::Space, Action::Released)]).is_empty());
+        assert_eq!(frame(&mut input, &mut detector, 0.1, vec![key(Key::Space, Action::Pressed)]), ["roll"]);
+    }
+
+    #[test]
+    fn holds_complete_once_per_press() {
+        let mut input = Input::new();
+        let mut detector = ComboDetector::new();
+        detector.add("charge", Combo::hold(Binding::key(Key::E), 0.5));
+
+        assert!(frame(&mut input, &mut detector, 0.0, vec![key(Key::E, Action::Pressed)]).is_empty());
+        assert!(frame(&mut input, &mut detector, 0.3, vec![]).is_empty());
+        assert_eq!(frame(&mut input, &mut detector, 0.3, vec![]), ["charge"]);
+        assert!(frame(&mut input, &mut detector, 0.3, vec![]).is_empty());
+
+        frame(&mut input, &mut detector, 0.3, vec![key(Key::E, Action::Released)]);
+        frame(&mut input, &mut detector, 0.0, vec![key(Key::E, Action::Pressed)]);
+        assert!(frame(&mut input, &mut detector, 0.3, vec![]).is_empty());
+        assert_eq!(frame(&mut input, &mut detector, 0.3, vec![]), ["charge"]);
+    }
+
+    #[test]
+    fn inverted_axes_are_held_in_the_other_direction() {
+        let mut input = Input::new();
+        let mut detector = ComboDetector::new();
+        detector.add("down", Combo::hold("-scroll:y".parse::<Binding>().unwrap(), 0.0));
+
+        assert!(frame(&mut input, &mut detector, 0.1, vec![InputEvent::Scroll(0.0, 1.0)]).is_empty());
+        assert_eq!(frame(&mut input, &mut detector, 0.1, vec![InputEvent::Scroll(0.0, -1.0)]), ["down"]);
+    }
+
+    #[test]
+    fn inverted_keys_are_rejected() {
+        let mut detector = ComboDetector::new();
+        detector.add("never", Combo::chord(vec!["-key:a".parse::<Binding>().unwrap()]));
+        assert!(detector.get_combo("never").is_none());
+
+        let source = "[combos.never]\ntype = \"double-tap\"\ninput = \"-mouse:left\"\nwindow = 0.2\n";
+        let file = ConfigFile::from_source("bindings.toml", source);
+        assert!(file.section::<CombosConfig>().is_err());
+    }
+
+    #[test]
+    fn combos_load_from_bindings_files() {
+        let source = "[combos.save]\ntype = \"chord\"\ninputs = [\"key:left-control\", \"key:s\"]\n\n\
+                      [combos.charge]\ntype = \"hold\"\ninput = \"gamepad:x\"\nduration = 1.5\n";
+        let config = ConfigFile::from_source("bindings.toml", source).section::<CombosConfig>().unwrap().unwrap();
+
+        let mut detector = ComboDetector::new();
+        detector.apply_combos(config);
+        assert_eq!(detector.get_combo_names(), ["charge", "save"]);
+        assert_eq!(detector.get_combo("save"), Some(&Combo::chord(vec![Binding::key(Key::LeftControl), Binding::key(Key::S)])));
+
+        let source = "[combos.dash]\ntype = \"sequence\"\ninputs = [\"key:down\"]\nwindow = 0\n";
+        assert!(ConfigFile::from_source("bindings.toml", source).section::<CombosConfig>().is_err());
+    }
+
+}
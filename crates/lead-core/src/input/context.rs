@@ -0,0 +1,327 @@
+use crate::input::action_map::InputSource;
+use crate::input::gamepad::GamepadButton;
+use crate::input::{Action, Input, Key, MouseButton};
+use crate::state::TState;
+use std::any::TypeId;
+use std::collections::VecDeque;
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+//                                          Input Block                                           //
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+// Inputs an active context hides from the contexts below it
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum InputBlock {
+    Source(InputSource),
+    // Every key along with the typed characters
+    Keyboard,
+    // Mouse buttons and scrolling, the cursor position stays visible
+    Mouse,
+    Gamepads,
+    All,
+}
+
+impl InputBlock {
+
+    fn blocks(&self, source: &InputSource) -> bool {
+        return match self {
+            InputBlock::Source(blocked) => blocked == source,
+            InputBlock::Keyboard => matches!(source, InputSource::Key(_)),
+            InputBlock::Mouse => matches!(source, InputSource::MouseButton(_) | InputSource::ScrollX | InputSource::ScrollY),
+            InputBlock::Gamepads => matches!(source, InputSource::GamepadButton(_) | InputSource::GamepadAxis(_)),
+            InputBlock::All => true,
+        }
+    }
+
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+//                                         Input Context                                          //
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+pub struct InputContext {
+    name: String,
+    priority: i32,
+    active: bool,
+    blocks: Vec<InputBlock>,
+    // Blocks added until the actions and combos of the next frame were evaluated, usually by a UI
+    // which the cursor hovers
+    consumed: Vec<InputBlock>,
+    // Active while one of these states is open
+    states: Vec<TypeId>,
+}
+
+impl InputContext {
+
+    fn new(name: &str, priority: i32) -> InputContext {
+        return InputContext {
+            name: name.to_string(),
+            priority,
+            active: false,
+            blocks: Vec::new(),
+            consumed: Vec::new(),
+            states: Vec::new(),
+        }
+    }
+
+    fn blocks(&self, filter: impl Fn(&InputBlock) -> bool) -> bool {
+        return self.active && self.blocks.iter().chain(self.consumed.iter()).any(filter);
+    }
+
+    /* ====================================== Getters ======================================= */
+
+    pub fn get_name(&self) -> &str {
+        return &self.name;
+    }
+
+    pub fn get_priority(&self) -> i32 {
+        return self.priority;
+    }
+
+    pub fn is_active(&self) -> bool {
+        return self.active;
+    }
+
+    pub fn get_blocks(&self) -> &[InputBlock] {
+        return &self.blocks;
+    }
+
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+//                                         Input Contexts                                         //
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+// Layers of input such as UI > Console > Gameplay. An active context hides the inputs it blocks or
+// consumes from every context with a lower priority, which then see them as Up
+pub struct InputContexts {
+    // Sorted by descending priority, contexts with the same priority keep their insertion order
+    contexts: Vec<InputContext>,
+}
+
+impl InputContexts {
+
+    pub(crate) fn new() -> InputContexts {
+        return InputContexts {
+            contexts: Vec::new(),
+        }
+    }
+
+    // Called once the actions and combos of the frame were evaluated
+    pub(crate) fn update(&mut self) {
+        for context in self.contexts.iter_mut() {
+            context.consumed.clear();
+        }
+    }
+
+    // Replaces the priority of an existing context, new contexts start inactive
+    pub fn add(&mut self, name: &str, priority: i32) {
+        let mut context = match self.contexts.iter().position(|context| context.name == name) {
+            Some(index) => self.contexts.remove(index),
+            None => InputContext::new(name, priority),
+        };
+        context.priority = priority;
+
+        let index = self.contexts.iter().position(|other| other.priority < priority).unwrap_or(self.contexts.len());
+        self.contexts.insert(index, context);
+    }
+
+    pub fn remove(&mut self, name: &str) -> bool {
+        let count = self.contexts.len();
+        self.contexts.retain(|context| context.name != name);
+        return self.contexts.len() != count;
+    }
+
+    pub fn set_active(&mut self, name: &str, active: bool) {
+        if let Some(context) = self.get_mut(name) {
+            context.active = active;
+        }
+    }
+
+    pub fn activate(&mut self, name: &str) {
+        self.set_active(name, true);
+    }
+
+    pub fn deactivate(&mut self, name: &str) {
+        self.set_active(name, false);
+    }
+
+    pub fn block(&mut self, name: &str, block: InputBlock) {
+        if let Some(context) = self.get_mut(name) {
+            if !context.blocks.contains(&block) {
+                context.blocks.push(block);
+            }
+        }
+    }
+
+    pub fn unblock(&mut self, name: &str, block: InputBlock) {
+        if let Some(context) = self.get_mut(name) {
+            context.blocks.retain(|blocked| *blocked != block);
+        }
+    }
+
+    // Hides the input from lower contexts for the rest of the frame and from the actions and combos
+    // evaluated at the start of the next one. A UI consumes the mouse while it is hovered, so the
+    // click which follows never reaches the actions of the game
+    pub fn consume(&mut self, name: &str, block: InputBlock) {
+        if let Some(context) = self.get_mut(name) {
+            context.consumed.push(block);
+        }
+    }
+
+    /* ======================================= States ======================================= */
+
+    // The context is activated when the state opens and deactivated when it closes
+    pub fn bind_state<State: TState + 'static>(&mut self, name: &str) {
+        if let Some(context) = self.get_mut(name) {
+            let state = TypeId::of::<State>();
+            if !context.states.contains(&state) {
+                context.states.push(state);
+            }
+        }
+    }
+
+    pub fn unbind_state<State: TState + 'static>(&mut self, name: &str) {
+        if let Some(context) = self.get_mut(name) {
+            context.states.retain(|state| *state != TypeId::of::<State>());
+        }
+    }
+
+    pub(crate) fn set_state_open(&mut self, state: TypeId, open: bool) {
+        for context in self.contexts.iter_mut().filter(|context| context.states.contains(&state)) {
+            context.active = open;
+        }
+    }
+
+    /* ====================================== Getters ======================================= */
+
+    pub fn get(&self, name: &str) -> Option<&InputContext> {
+        return self.contexts.iter().find(|context| context.name == name);
+    }
+
+    fn get_mut(&mut self, name: &str) -> Option<&mut InputContext> {
+        return self.contexts.iter_mut().find(|context| context.name == name);
+    }
+
+    // Highest priority first
+    pub fn iter(&self) -> impl Iterator<Item = &InputContext> {
+        return self.contexts.iter();
+    }
+
+    pub fn is_active(&self, name: &str) -> bool {
+        return self.get(name).map(|context| context.active).unwrap_or(false);
+    }
+
+    // Whether a context with a higher priority hides the source, unknown contexts only see
+    // unblocked inputs
+    pub fn is_blocked(&self, name: &str, source: &InputSource) -> bool {
+        return self.is_hidden(name, |block| block.blocks(source));
+    }
+
+    pub fn is_keyboard_blocked(&self, name: &str) -> bool {
+        return self.is_hidden(name, |block| matches!(block, InputBlock::Keyboard | InputBlock::All));
+    }
+
+    fn is_hidden(&self, name: &str, filter: impl Fn(&InputBlock) -> bool) -> bool {
+        let priority = self.get(name).map(|context| context.priority).unwrap_or(i32::MIN);
+        return self.contexts.iter()
+            .take_while(|context| context.priority > priority)
+            .any(|context| context.blocks(&filter));
+    }
+
+}
+
+impl Default for InputContexts {
+
+    fn default() -> Self {
+        return InputContexts::new();
+    }
+
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+//                                         Context Input                                          //
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+// The input as seen from one context, blocked inputs are reported as Up or 0
+pub struct ContextInput<'a> {
+    input: &'a Input,
+    name: &'a str,
+}
+
+impl<'a> ContextInput<'a> {
+
+    pub(crate) fn new(input: &'a Input, name: &'a str) -> ContextInput<'a> {
+        return ContextInput {
+            input,
+            name,
+        }
+    }
+
+    pub fn is_blocked(&self, source: &InputSource) -> bool {
+        return self.input.contexts.is_blocked(self.name, source);
+    }
+
+    pub fn get_key(&self, key: Key) -> Action {
+        if self.is_blocked(&InputSource::Key(key)) {
+            return Action::Up;
+        }
+        return self.input.keyboard.get_key(key);
+    }
+
+    pub fn get_mouse_button(&self, button: MouseButton) -> Action {
+        if self.is_blocked(&InputSource::MouseButton(button)) {
+            return Action::Up;
+        }
+        return self.input.mouse.get_button(button);
+    }
+
+    pub fn get_scroll_x(&self) -> f64 {
+        if self.is_blocked(&InputSource::ScrollX) {
+            return 0.0;
+        }
+        return self.input.mouse.get_scroll_x();
+    }
+
+    pub fn get_scroll_y(&self) -> f64 {
+        if self.is_blocked(&InputSource::ScrollY) {
+            return 0.0;
+        }
+        return self.input.mouse.get_scroll_y();
+    }
+
+    // Button of the first gamepad with a mapping
+    pub fn get_gamepad_button(&self, button: GamepadButton) -> Action {
+        if self.is_blocked(&InputSource::GamepadButton(button)) {
+            return Action::Up;
+        }
+        return self.input.gamepads.get_first().map(|gamepad| gamepad.get_button(button)).unwrap_or_default();
+    }
+
+    pub fn get_value(&self, source: &InputSource) -> f32 {
+        if self.is_blocked(source) {
+            return 0.0;
+        }
+        return source.get_value(self.input);
+    }
+
+    // Characters are blocked along with the keyboard
+    pub fn get_chars(&self) -> VecDeque<char> {
+        if self.input.contexts.is_keyboard_blocked(self.name) {
+            return VecDeque::new();
+        }
+        return self.input.keyboard.get_chars();
+    }
+
+    /* ====================================== Getters ======================================= */
+
+    pub fn get_name(&self) -> &str {
+        return self.name;
+    }
+
+    // The cursor position and everything else which is never blocked
+    pub fn get_input(&self) -> &Input {
+        return self.input;
+    }
+
+}
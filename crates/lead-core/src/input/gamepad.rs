@@ -0,0 +1,422 @@
+pub use glfw::{GamepadAxis, GamepadButton, JoystickId};
+use crate::input::Action;
+use glfw::Glfw;
+use std::fmt::{Display, Formatter};
+use std::path::{Path, PathBuf};
+
+pub const MAX_GAMEPADS: usize = (glfw::ffi::JOYSTICK_LAST + 1) as usize;
+pub const BUTTON_COUNT: usize = (glfw::ffi::GAMEPAD_BUTTON_LAST + 1) as usize;
+pub const AXIS_COUNT: usize = (glfw::ffi::GAMEPAD_AXIS_LAST + 1) as usize;
+
+pub const DEFAULT_STICK_DEAD_ZONE: f32 = 0.15;
+pub const DEFAULT_TRIGGER_DEAD_ZONE: f32 = 0.05;
+
+fn is_trigger(axis: GamepadAxis) -> bool {
+    return matches!(axis, GamepadAxis::AxisLeftTrigger | GamepadAxis::AxisRightTrigger);
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+//                                         Gamepad Event                                          //
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+// Buttons only carry Action::Pressed and Action::Released, axes carry the value before the dead
+// zone is applied
+#[derive(Clone, PartialEq, Debug)]
+pub enum GamepadEvent {
+    Connected {
+        id: JoystickId,
+        name: String,
+        guid: String,
+        mapped: bool,
+    },
+    Disconnected(JoystickId),
+    Button(JoystickId, GamepadButton, Action),
+    Axis(JoystickId, GamepadAxis, f32),
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+//                                         Mapping Error                                          //
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+#[derive(Debug)]
+pub enum MappingError {
+    Io {
+        path: PathBuf,
+        error: std::io::Error,
+    },
+    // GLFW refused the database, the reason is logged by GLFW itself
+    Rejected,
+    // Mappings can not be loaded without a native window
+    Headless,
+}
+
+impl Display for MappingError {
+
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        return match self {
+            MappingError::Io { path, error } => write!(f, "Failed to read '{}': {}", path.display(), error),
+            MappingError::Rejected => write!(f, "Invalid gamepad mappings"),
+            MappingError::Headless => write!(f, "Gamepad mappings are not available without a window"),
+        }
+    }
+
+}
+
+impl std::error::Error for MappingError {}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+//                                            Gamepad                                             //
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+pub struct Gamepad {
+    id: JoystickId,
+    name: String,
+    guid: String,
+    // False for joysticks without a gamepad mapping, their buttons and axes are never updated
+    mapped: bool,
+    buttons: [Action; BUTTON_COUNT],
+    axes: [f32; AXIS_COUNT],
+    raw_axes: [f32; AXIS_COUNT],
+}
+
+impl Gamepad {
+
+    pub(crate) fn new(id: JoystickId, name: String, guid: String, mapped: bool) -> Gamepad {
+        return Gamepad {
+            id,
+            name,
+            guid,
+            mapped,
+            buttons: [Action::default(); BUTTON_COUNT],
+            axes: [0.0; AXIS_COUNT],
+            raw_axes: [0.0; AXIS_COUNT],
+        }
+    }
+
+    fn update(&mut self) {
+        for button in self.buttons.iter_mut() {
+            match button {
+                Action::Pressed => *button = Action::Down,
+                Action::Released => *button = Action::Up,
+                _ => {}
+            }
+        }
+    }
+
+    fn set_axis(&mut self, axis: GamepadAxis, value: f32, dead_zone: f32) {
+        self.raw_axes[axis as usize] = value;
+
+        let magnitude = value.abs();
+        self.axes[axis as usize] = if magnitude <= dead_zone || dead_zone >= 1.0 {
+            0.0
+        } else {
+            value.signum() * ((magnitude - dead_zone) / (1.0 - dead_zone)).min(1.0)
+        };
+    }
+
+    /* ====================================== Getters ======================================= */
+
+    pub fn get_id(&self) -> JoystickId {
+        return self.id;
+    }
+
+    pub fn get_name(&self) -> &str {
+        return &self.name;
+    }
+
+    pub fn get_guid(&self) -> &str {
+        return &self.guid;
+    }
+
+    pub fn is_mapped(&self) -> bool {
+        return self.mapped;
+    }
+
+    pub fn get_button(&self, button: GamepadButton) -> Action {
+        return self.buttons[button as usize];
+    }
+
+    // Value with the dead zone applied, rescaled to use the whole range
+    pub fn get_axis(&self, axis: GamepadAxis) -> f32 {
+        return self.axes[axis as usize];
+    }
+
+    pub fn get_raw_axis(&self, axis: GamepadAxis) -> f32 {
+        return self.raw_axes[axis as usize];
+    }
+
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+//                                            Gamepads                                            //
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+pub struct Gamepads {
+    glfw: Option<Glfw>,
+    slots: Vec<Option<Gamepad>>,
+    dead_zones: [f32; AXIS_COUNT],
+    events: Vec<GamepadEvent>,
+}
+
+impl Gamepads {
+
+    pub(crate) fn new() -> Gamepads {
+        let mut dead_zones = [DEFAULT_STICK_DEAD_ZONE; AXIS_COUNT];
+        dead_zones[GamepadAxis::AxisLeftTrigger as usize] = DEFAULT_TRIGGER_DEAD_ZONE;
+        dead_zones[GamepadAxis::AxisRightTrigger as usize] = DEFAULT_TRIGGER_DEAD_ZONE;
+
+        return Gamepads {
+            glfw: None,
+            slots: (0..MAX_GAMEPADS).map(|_| None).collect(),
+            dead_zones,
+            events: Vec::new(),
+        }
+    }
+
+    // Without glfw, gamepads are never polled
+    pub(crate) fn set_glfw(&mut self, glfw: Option<Glfw>) {
+        self.glfw = glfw;
+    }
+
+    pub(crate) fn update(&mut self) {
+        self.events.clear();
+        for gamepad in self.slots.iter_mut().flatten() {
+            gamepad.update();
+        }
+    }
+
+    // Changes since the last applied state, as events to apply through Input
+    pub(crate) fn poll(&self) -> Vec<GamepadEvent> {
+        let mut events = Vec::new();
+        let glfw = match &self.glfw {
+            Some(glfw) => glfw,
+            None => return events,
+        };
+
+        for id in (0..MAX_GAMEPADS as i32).filter_map(JoystickId::from_i32) {
+            let joystick = glfw.get_joystick(id);
+            let current = self.slots[id as usize].as_ref();
+
+            if !joystick.is_present() {
+                if current.is_some() {
+                    events.push(GamepadEvent::Disconnected(id));
+                }
+                continue;
+            }
+
+            // A mapping added or replaced after the gamepad was connected reconnects it
+            let mapped = joystick.is_gamepad();
+            let name = joystick.get_gamepad_name().or_else(|| joystick.get_name()).unwrap_or_default();
+            if current.map(|gamepad| gamepad.mapped != mapped || gamepad.name != name).unwrap_or(true) {
+                events.push(GamepadEvent::Connected {
+                    id,
+                    name,
+                    guid: joystick.get_guid().unwrap_or_default(),
+                    mapped,
+                });
+            }
+
+            let state = match joystick.get_gamepad_state() {
+                Some(state) => state,
+                None => continue,
+            };
+            for button in (0..BUTTON_COUNT as i32).filter_map(GamepadButton::from_i32) {
+                let down = state.get_button_state(button) != glfw::Action::Release;
+                let was_down = current.map(|gamepad| gamepad.get_button(button).is_pressed_or_down()).unwrap_or(false);
+                if down != was_down {
+                    events.push(GamepadEvent::Button(id, button, if down { Action::Pressed } else { Action::Released }));
+                }
+            }
+            for axis in (0..AXIS_COUNT as i32).filter_map(GamepadAxis::from_i32) {
+                // Triggers are reported from 0 (released) to 1, sticks from -1 to 1
+                let raw = state.get_axis(axis);
+                let value = if is_trigger(axis) { (raw + 1.0) * 0.5 } else { raw };
+                if current.map(|gamepad| gamepad.get_raw_axis(axis)).unwrap_or(0.0) != value {
+                    events.push(GamepadEvent::Axis(id, axis, value));
+                }
+            }
+        }
+
+        return events;
+    }
+
+    pub(crate) fn apply(&mut self, event: &GamepadEvent) {
+        match event {
+            GamepadEvent::Connected { id, name, guid, mapped } => {
+                // Reconnected after a mapping change, the held buttons and axes carry over
+                match &mut self.slots[*id as usize] {
+                    Some(gamepad) => {
+                        gamepad.name = name.clone();
+                        gamepad.guid = guid.clone();
+                        gamepad.mapped = *mapped;
+                    }
+                    slot => *slot = Some(Gamepad::new(*id, name.clone(), guid.clone(), *mapped)),
+                }
+                self.events.push(event.clone());
+            }
+            GamepadEvent::Disconnected(id) => {
+                self.slots[*id as usize] = None;
+                self.events.push(event.clone());
+            }
+            GamepadEvent::Button(id, button, action) => {
+                if let Some(gamepad) = &mut self.slots[*id as usize] {
+                    gamepad.buttons[*button as usize] = *action;
+                }
+            }
+            GamepadEvent::Axis(id, axis, value) => {
+                let dead_zone = self.dead_zones[*axis as usize];
+                if let Some(gamepad) = &mut self.slots[*id as usize] {
+                    gamepad.set_axis(*axis, *value, dead_zone);
+                }
+            }
+        }
+    }
+
+    /* ====================================== Mappings ====================================== */
+
+    // Mappings use the SDL_GameControllerDB format, one controller per line
+    pub fn update_mappings(&self, mappings: &str) -> Result<(), MappingError> {
+        let glfw = self.glfw.as_ref().ok_or(MappingError::Headless)?;
+        if !glfw.update_gamepad_mappings(mappings) {
+            return Err(MappingError::Rejected);
+        }
+        return Ok(());
+    }
+
+    pub fn load_mappings<P: AsRef<Path>>(&self, path: P) -> Result<(), MappingError> {
+        let path = path.as_ref();
+        let mappings = std::fs::read_to_string(path).map_err(|error| MappingError::Io { path: path.to_path_buf(), error })?;
+        return self.update_mappings(&mappings);
+    }
+
+    /* ===================================== Dead Zones ===================================== */
+
+    pub fn get_dead_zone(&self, axis: GamepadAxis) -> f32 {
+        return self.dead_zones[axis as usize];
+    }
+
+    // Applies to the current position of the axis as well, without waiting for it to move
+    pub fn set_dead_zone(&mut self, axis: GamepadAxis, dead_zone: f32) {
+        let dead_zone = dead_zone.clamp(0.0, 1.0);
+        self.dead_zones[axis as usize] = dead_zone;
+        for gamepad in self.slots.iter_mut().flatten() {
+            gamepad.set_axis(axis, gamepad.raw_axes[axis as usize], dead_zone);
+        }
+    }
+
+    pub fn set_stick_dead_zone(&mut self, dead_zone: f32) {
+        for axis in [GamepadAxis::AxisLeftX, GamepadAxis::AxisLeftY, GamepadAxis::AxisRightX, GamepadAxis::AxisRightY] {
+            self.set_dead_zone(axis, dead_zone);
+        }
+    }
+
+    pub fn set_trigger_dead_zone(&mut self, dead_zone: f32) {
+        self.set_dead_zone(GamepadAxis::AxisLeftTrigger, dead_zone);
+        self.set_dead_zone(GamepadAxis::AxisRightTrigger, dead_zone);
+    }
+
+    /* ====================================== Getters ======================================= */
+
+    pub fn get(&self, id: JoystickId) -> Option<&Gamepad> {
+        return self.slots[id as usize].as_ref();
+    }
+
+    // Connected gamepads, ordered by id
+    pub fn iter(&self) -> impl Iterator<Item = &Gamepad> {
+        return self.slots.iter().flatten();
+    }
+
+    // The first connected gamepad with a mapping, usually the one of the first player
+    pub fn get_first(&self) -> Option<&Gamepad> {
+        return self.iter().find(|gamepad| gamepad.is_mapped());
+    }
+
+    pub fn is_connected(&self, id: JoystickId) -> bool {
+        return self.slots[id as usize].is_some();
+    }
+
+    // Connections and disconnections of this frame
+    pub fn get_events(&self) -> &[GamepadEvent] {
+        return &self.events;
+    }
+
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+//                                             Tests                                              //
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn connected(name: &str) -> GamepadEvent {
+        return GamepadEvent::Connected { id: JoystickId::Joystick1, name: name.to_string(), guid: "0300".to_string(), mapped: true }
+    }
+
+    #[test]
+    fn buttons_move_from_pressed_to_down() {
+        let mut gamepads = Gamepads::new();
+        gamepads.apply(&connected("Pad"));
+        gamepads.apply(&GamepadEvent::Button(JoystickId::Joystick1, GamepadButton::ButtonA, Action::Pressed));
+
+        let gamepad = gamepads.get_first().unwrap();
+        assert_eq!(gamepad.get_button(GamepadButton::ButtonA), Action::Pressed);
+        assert_eq!(gamepads.get_events().len(), 1);
+
+        gamepads.update();
+        assert_eq!(gamepads.get(JoystickId::Joystick1).unwrap().get_button(GamepadButton::ButtonA), Action::Down);
+        assert!(gamepads.get_events().is_empty());
+    }
+
+    #[test]
+    fn dead_zones_apply_to_applied_axes() {
+        let mut gamepads = Gamepads::new();
+        gamepads.set_stick_dead_zone(0.2);
+        gamepads.apply(&connected("Pad"));
+        gamepads.apply(&GamepadEvent::Axis(JoystickId::Joystick1, GamepadAxis::AxisLeftX, 0.1));
+
+        let gamepad = gamepads.get_first().unwrap();
+        assert_eq!(gamepad.get_axis(GamepadAxis::AxisLeftX), 0.0);
+        assert_eq!(gamepad.get_raw_axis(GamepadAxis::AxisLeftX), 0.1);
+    }
+
+    #[test]
+    fn dead_zone_changes_apply_to_the_current_axes() {
+        let mut gamepads = Gamepads::new();
+        gamepads.apply(&connected("Pad"));
+        gamepads.apply(&GamepadEvent::Axis(JoystickId::Joystick1, GamepadAxis::AxisLeftX, 0.1));
+        gamepads.apply(&GamepadEvent::Axis(JoystickId::Joystick1, GamepadAxis::AxisLeftTrigger, 0.625));
+
+        gamepads.set_stick_dead_zone(0.0);
+        gamepads.set_trigger_dead_zone(0.25);
+        let gamepad = gamepads.get_first().unwrap();
+        assert_eq!(gamepad.get_axis(GamepadAxis::AxisLeftX), 0.1);
+        assert_eq!(gamepad.get_axis(GamepadAxis::AxisLeftTrigger), 0.5);
+
+        gamepads.set_dead_zone(GamepadAxis::AxisLeftX, 0.5);
+        assert_eq!(gamepads.get_first().unwrap().get_axis(GamepadAxis::AxisLeftX), 0.0);
+        assert_eq!(gamepads.get_first().unwrap().get_raw_axis(GamepadAxis::AxisLeftX), 0.1);
+    }
+
+    #[test]
+    fn mapping_changes_keep_the_held_buttons() {
+        let mut gamepads = Gamepads::new();
+        gamepads.apply(&connected("Pad"));
+        gamepads.apply(&GamepadEvent::Button(JoystickId::Joystick1, GamepadButton::ButtonA, Action::Pressed));
+        gamepads.apply(&GamepadEvent::Axis(JoystickId::Joystick1, GamepadAxis::AxisRightTrigger, 1.0));
+        gamepads.update();
+
+        gamepads.apply(&connected("Remapped Pad"));
+        let gamepad = gamepads.get_first().unwrap();
+        assert_eq!(gamepad.get_name(), "Remapped Pad");
+        assert_eq!(gamepad.get_button(GamepadButton::ButtonA), Action::Down);
+        assert_eq!(gamepad.get_raw_axis(GamepadAxis::AxisRightTrigger), 1.0);
+        assert_eq!(gamepads.get_events(), [connected("Remapped Pad")]);
+
+        gamepads.apply(&GamepadEvent::Disconnected(JoystickId::Joystick1));
+        gamepads.apply(&connected("Pad"));
+        assert_eq!(gamepads.get_first().unwrap().get_button(GamepadButton::ButtonA), Action::Up);
+    }
+
+}
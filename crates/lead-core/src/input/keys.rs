@@ -0,0 +1,125 @@
+use glfw::{GamepadAxis, GamepadButton, Key, Modifiers, MouseButton};
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+//                                              Keys                                              //
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+pub const KEYS: [Key; 120] = [
+    Key::Space, Key::Apostrophe, Key::Comma, Key::Minus, Key::Period, Key::Slash,
+    Key::Num0, Key::Num1, Key::Num2, Key::Num3, Key::Num4, Key::Num5, Key::Num6, Key::Num7, Key::Num8, Key::Num9,
+    Key::Semicolon, Key::Equal,
+    Key::A, Key::B, Key::C, Key::D, Key::E, Key::F, Key::G, Key::H, Key::I, Key::J, Key::K, Key::L, Key::M,
+    Key::N, Key::O, Key::P, Key::Q, Key::R, Key::S, Key::T, Key::U, Key::V, Key::W, Key::X, Key::Y, Key::Z,
+    Key::LeftBracket, Key::Backslash, Key::RightBracket, Key::GraveAccent, Key::World1, Key::World2,
+    Key::Escape, Key::Enter, Key::Tab, Key::Backspace, Key::Insert, Key::Delete,
+    Key::Right, Key::Left, Key::Down, Key::Up, Key::PageUp, Key::PageDown, Key::Home, Key::End,
+    Key::CapsLock, Key::ScrollLock, Key::NumLock, Key::PrintScreen, Key::Pause,
+    Key::F1, Key::F2, Key::F3, Key::F4, Key::F5, Key::F6, Key::F7, Key::F8, Key::F9, Key::F10, Key::F11, Key::F12, Key::F13,
+    Key::F14, Key::F15, Key::F16, Key::F17, Key::F18, Key::F19, Key::F20, Key::F21, Key::F22, Key::F23, Key::F24, Key::F25,
+    Key::Kp0, Key::Kp1, Key::Kp2, Key::Kp3, Key::Kp4, Key::Kp5, Key::Kp6, Key::Kp7, Key::Kp8, Key::Kp9,
+    Key::KpDecimal, Key::KpDivide, Key::KpMultiply, Key::KpSubtract, Key::KpAdd, Key::KpEnter, Key::KpEqual,
+    Key::LeftShift, Key::LeftControl, Key::LeftAlt, Key::LeftSuper,
+    Key::RightShift, Key::RightControl, Key::RightAlt, Key::RightSuper, Key::Menu,
+];
+
+// Stable name of a key, independent from the keyboard layout: "space", "left-shift", "kp-add"
+pub fn key_name(key: Key) -> String {
+    return to_kebab_case(&format!("{:?}", key));
+}
+
+pub fn key_from_name(name: &str) -> Option<Key> {
+    return KEYS.iter().copied().find(|key| key_name(*key).eq_ignore_ascii_case(name));
+}
+
+// Label of a key in English, independent from the keyboard layout: "Space", "Left Shift", "Kp Add"
+pub fn key_label(key: Key) -> String {
+    return key_name(key)
+        .split('-')
+        .map(|word| {
+            let mut chars = word.chars();
+            match chars.next() {
+                Some(first) => first.to_ascii_uppercase().to_string() + chars.as_str(),
+                None => String::new(),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ");
+}
+
+const MODIFIERS: [(Modifiers, &str); 6] = [
+    (Modifiers::Shift, "shift"),
+    (Modifiers::Control, "control"),
+    (Modifiers::Alt, "alt"),
+    (Modifiers::Super, "super"),
+    (Modifiers::CapsLock, "caps-lock"),
+    (Modifiers::NumLock, "num-lock"),
+];
+
+// "shift+control", empty without any modifier
+pub fn modifiers_name(modifiers: Modifiers) -> String {
+    return MODIFIERS.iter()
+        .filter(|(modifier, _)| modifiers.contains(*modifier))
+        .map(|(_, name)| *name)
+        .collect::<Vec<_>>()
+        .join("+");
+}
+
+pub fn modifiers_from_name(name: &str) -> Option<Modifiers> {
+    let mut modifiers = Modifiers::empty();
+    for part in name.split('+').filter(|part| !part.is_empty()) {
+        let (modifier, _) = MODIFIERS.iter().find(|(_, modifier_name)| modifier_name.eq_ignore_ascii_case(part))?;
+        modifiers |= *modifier;
+    }
+    return Some(modifiers);
+}
+
+// "left", "right", "middle", then "button4" to "button8"
+pub fn mouse_button_name(button: MouseButton) -> String {
+    return match button {
+        MouseButton::Button1 => "left".to_string(),
+        MouseButton::Button2 => "right".to_string(),
+        MouseButton::Button3 => "middle".to_string(),
+        _ => format!("button{}", button as i32 + 1),
+    }
+}
+
+pub fn mouse_button_from_name(name: &str) -> Option<MouseButton> {
+    return (0..=glfw::ffi::MOUSE_BUTTON_LAST)
+        .filter_map(MouseButton::from_i32)
+        .find(|button| mouse_button_name(*button).eq_ignore_ascii_case(name));
+}
+
+// "a", "left-bumper", "dpad-up"
+pub fn gamepad_button_name(button: GamepadButton) -> String {
+    let name = format!("{:?}", button);
+    return to_kebab_case(name.trim_start_matches("Button"));
+}
+
+pub fn gamepad_button_from_name(name: &str) -> Option<GamepadButton> {
+    return (0..=glfw::ffi::GAMEPAD_BUTTON_LAST)
+        .filter_map(GamepadButton::from_i32)
+        .find(|button| gamepad_button_name(*button).eq_ignore_ascii_case(name));
+}
+
+// "left-x", "right-y", "left-trigger"
+pub fn gamepad_axis_name(axis: GamepadAxis) -> String {
+    let name = format!("{:?}", axis);
+    return to_kebab_case(name.trim_start_matches("Axis"));
+}
+
+pub fn gamepad_axis_from_name(name: &str) -> Option<GamepadAxis> {
+    return (0..=glfw::ffi::GAMEPAD_AXIS_LAST)
+        .filter_map(GamepadAxis::from_i32)
+        .find(|axis| gamepad_axis_name(*axis).eq_ignore_ascii_case(name));
+}
+
+fn to_kebab_case(name: &str) -> String {
+    let mut result = String::with_capacity(name.len() + 4);
+    for (i, chr) in name.chars().enumerate() {
+        if chr.is_ascii_uppercase() && i > 0 {
+            result.push('-');
+        }
+        result.push(chr.to_ascii_lowercase());
+    }
+    return result;
+}
@@ -0,0 +1,799 @@
+pub use glfw::{Key, Modifiers, MouseButton, MouseButtonLeft, MouseButtonRight, MouseButtonMiddle, Scancode};
+use crate::clipboard::ClipboardAction;
+use crate::input::context::{ContextInput, InputContexts};
+use crate::input::gamepad::{GamepadEvent, Gamepads};
+use crate::input::keys::key_label;
+use crate::input::recording::{InputRecording, Replay};
+use glfw::Glfw;
+use lead_logger::info;
+use std::collections::{HashMap, VecDeque};
+use std::path::PathBuf;
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+//                                            Modules                                             //
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+pub mod keys;
+pub mod action_map;
+pub mod combo;
+pub mod context;
+pub mod gamepad;
+pub mod recording;
+pub mod text;
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+//                                             Input                                              //
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(feature = "globals")]
+pub fn get_input() -> lead_mem::pointer::MutPointer<Input> {
+    return lead_mem::pointer::MutPointer::new(crate::get_engine().as_mut().context_mut().input_mut());
+}
+
+pub struct Input {
+    pub(super) mouse: Mouse,
+    pub(super) keyboard: Keyboard,
+    pub(super) gamepads: Gamepads,
+    pub(super) contexts: InputContexts,
+    pub(super) dropped_files: Vec<PathBuf>,
+    // Every event of the current frame in the order they happened
+    events: Vec<TimedInputEvent>,
+    glfw: Option<Glfw>,
+    // Sum of the frame deltas, the clock of events without glfw
+    time: f64,
+    recording: Option<InputRecording>,
+    // Events of the current frame, pushed to the recording at the end of the frame
+    recorded_events: Vec<InputEvent>,
+    replay: Option<Replay>,
+    injected: Vec<InputEvent>,
+}
+
+impl Input {
+
+    pub(crate) fn new() -> Input {
+        return Input {
+            mouse: Mouse::new(),
+            keyboard: Keyboard::new(),
+            gamepads: Gamepads::new(),
+            contexts: InputContexts::new(),
+            dropped_files: Vec::new(),
+            events: Vec::new(),
+            glfw: None,
+            time: 0.0,
+            recording: None,
+            recorded_events: Vec::new(),
+            replay: None,
+            injected: Vec::new(),
+        }
+    }
+
+    // Without glfw, keys have no scancodes and no layout dependent names, and gamepads are never
+    // polled
+    pub(crate) fn set_glfw(&mut self, glfw: Option<Glfw>) {
+        self.keyboard.glfw = glfw.clone();
+        self.gamepads.set_glfw(glfw.clone());
+        self.glfw = glfw;
+    }
+
+    pub(super) fn update(&mut self) {
+        self.dropped_files.clear();
+        self.events.clear();
+        self.mouse.update();
+        self.keyboard.update();
+        self.gamepads.update();
+    }
+
+    // Seconds since GLFW was initialized, or the sum of the frame deltas without a window
+    pub fn get_time(&self) -> f64 {
+        return match &self.glfw {
+            Some(glfw) => glfw.get_time(),
+            None => self.time,
+        }
+    }
+
+    // Returns the event to publish, live events are dropped while a replay is running
+    pub(crate) fn handle_event(&mut self, time: f64, event: InputEvent) -> Option<InputEvent> {
+        if self.replay.is_some() {
+            return None;
+        }
+
+        self.apply(time, &event);
+        if self.recording.is_some() {
+            self.recorded_events.push(event.clone());
+        }
+        return Some(event);
+    }
+
+    pub(crate) fn take_injected(&mut self) -> Vec<InputEvent> {
+        return std::mem::take(&mut self.injected);
+    }
+
+    fn apply(&mut self, time: f64, event: &InputEvent) {
+        self.events.push(TimedInputEvent { time, event: event.clone() });
+        match event {
+            InputEvent::Key(key, scancode, action, modifiers) => self.keyboard.set_key(*key, *scancode, *action, *modifiers),
+            InputEvent::KeyRepeat(key, _, modifiers) => {
+                self.keyboard.repeats.push(*key);
+                self.keyboard.lock_modifiers = *modifiers & (Modifiers::CapsLock | Modifiers::NumLock);
+            }
+            InputEvent::MouseButton(button, action) => self.mouse.buttons[*button as usize] = *action,
+            InputEvent::CursorMoved(x, y) => self.mouse.move_to(*x, *y),
+            InputEvent::CursorEntered(cursor_in) => self.mouse.cursor_in = *cursor_in,
+            InputEvent::Scroll(x, y) => {
+                self.mouse.scroll_x += *x;
+                self.mouse.scroll_y += *y;
+            }
+            InputEvent::Char(chr) => self.keyboard.chars.push_back(*chr),
+            InputEvent::FileDropped(path) => self.dropped_files.push(path.clone()),
+            InputEvent::Gamepad(event) => self.gamepads.apply(event),
+        }
+    }
+
+    // Called once every event of the frame was handled. While replaying, the recorded events are
+    // applied instead, stamped with the time of the frame, and returned for publishing along with
+    // the recorded delta
+    pub(crate) fn end_frame(&mut self, delta: f64) -> (f64, Vec<InputEvent>) {
+        let (delta, events) = self.replay_frame(delta);
+        self.time += delta;
+        return (delta, events);
+    }
+
+    fn replay_frame(&mut self, delta: f64) -> (f64, Vec<InputEvent>) {
+        if let Some(replay) = &mut self.replay {
+            let frame = match replay.next_frame() {
+                Some(frame) => frame,
+                None => return (delta, Vec::new()),
+            };
+            if replay.is_finished() {
+                info!("Input", "Replay finished");
+                self.replay = None;
+            }
+
+            let time = self.get_time();
+            let events = frame.get_events().to_vec();
+            for event in events.iter() {
+                self.apply(time, event);
+            }
+            if let Some(recording) = &mut self.recording {
+                recording.push_frame(frame.get_delta(), events.clone());
+            }
+            return (frame.get_delta(), events);
+        }
+
+        if let Some(recording) = &mut self.recording {
+            recording.push_frame(delta, std::mem::take(&mut self.recorded_events));
+        }
+        return (delta, Vec::new());
+    }
+
+    /* ===================================== Injection ====================================== */
+
+    // Injected events are handled with the window events of the next frame, as if they came from
+    // GLFW. Actions should only be Action::Pressed or Action::Released
+    pub fn inject(&mut self, event: InputEvent) {
+        self.injected.push(event);
+    }
+
+    // Uses the scancode of the key if known and the current modifiers
+    pub fn press_key(&mut self, key: Key) {
+        let scancode = self.keyboard.get_scancode(key).unwrap_or(-1);
+        self.inject(InputEvent::Key(key, scancode, Action::Pressed, self.keyboard.get_modifiers()));
+    }
+
+    pub fn release_key(&mut self, key: Key) {
+        let scancode = self.keyboard.get_scancode(key).unwrap_or(-1);
+        self.inject(InputEvent::Key(key, scancode, Action::Released, self.keyboard.get_modifiers()));
+    }
+
+    pub fn repeat_key(&mut self, key: Key) {
+        let scancode = self.keyboard.get_scancode(key).unwrap_or(-1);
+        self.inject(InputEvent::KeyRepeat(key, scancode, self.keyboard.get_modifiers()));
+    }
+
+    pub fn press_mouse_button(&mut self, button: MouseButton) {
+        self.inject(InputEvent::MouseButton(button, Action::Pressed));
+    }
+
+    pub fn release_mouse_button(&mut self, button: MouseButton) {
+        self.inject(InputEvent::MouseButton(button, Action::Released));
+    }
+
+    pub fn move_mouse(&mut self, x: f64, y: f64) {
+        self.inject(InputEvent::CursorMoved(x, y));
+    }
+
+    pub fn set_cursor_in(&mut self, cursor_in: bool) {
+        self.inject(InputEvent::CursorEntered(cursor_in));
+    }
+
+    pub fn scroll(&mut self, x: f64, y: f64) {
+        self.inject(InputEvent::Scroll(x, y));
+    }
+
+    pub fn type_text(&mut self, text: &str) {
+        for chr in text.chars() {
+            self.inject(InputEvent::Char(chr));
+        }
+    }
+
+    pub fn drop_file<P: Into<PathBuf>>(&mut self, path: P) {
+        self.inject(InputEvent::FileDropped(path.into()));
+    }
+
+    pub fn has_injected(&self) -> bool {
+        return !self.injected.is_empty();
+    }
+
+    /* ===================================== Recording ====================================== */
+
+    pub fn start_recording(&mut self) {
+        self.recording = Some(InputRecording::new());
+        self.recorded_events.clear();
+    }
+
+    // The current frame is not part of the recording until it ends
+    pub fn stop_recording(&mut self) -> Option<InputRecording> {
+        self.recorded_events.clear();
+        return self.recording.take();
+    }
+
+    pub fn is_recording(&self) -> bool {
+        return self.recording.is_some();
+    }
+
+    // Replaces the live events with the recorded ones, starting with the next frame. Replaying
+    // from a fresh input state, such as a new engine, reproduces the recorded session
+    pub fn start_replay(&mut self, recording: InputRecording) {
+        if recording.is_empty() {
+            return;
+        }
+        self.replay = Some(Replay::new(recording));
+    }
+
+    pub fn stop_replay(&mut self) {
+        self.replay = None;
+    }
+
+    pub fn is_replaying(&self) -> bool {
+        return self.replay.is_some();
+    }
+
+    /* ====================================== Getters ======================================= */
+
+    pub fn mouse(&self) -> &Mouse {
+        return &self.mouse;
+    }
+
+    pub fn mouse_mut(&mut self) -> &mut Mouse {
+        return &mut self.mouse;
+    }
+
+    pub fn keyboard(&self) -> &Keyboard {
+        return &self.keyboard;
+    }
+
+    pub fn gamepads(&self) -> &Gamepads {
+        return &self.gamepads;
+    }
+
+    pub fn gamepads_mut(&mut self) -> &mut Gamepads {
+        return &mut self.gamepads;
+    }
+
+    pub fn contexts(&self) -> &InputContexts {
+        return &self.contexts;
+    }
+
+    pub fn contexts_mut(&mut self) -> &mut InputContexts {
+        return &mut self.contexts;
+    }
+
+    // The input as seen by a context, without the inputs blocked by higher contexts
+    pub fn context<'a>(&'a self, name: &'a str) -> ContextInput<'a> {
+        return ContextInput::new(self, name);
+    }
+
+    pub fn get_dropped_files(&self) -> Vec<PathBuf> {
+        return self.dropped_files.clone();
+    }
+
+    pub fn get_events(&self) -> &[TimedInputEvent] {
+        return &self.events;
+    }
+
+    // Also true for a key pressed and released within the same frame, which ends Released
+    pub fn was_key_pressed(&self, key: Key) -> bool {
+        return self.events.iter().any(|timed| matches!(timed.event, InputEvent::Key(pressed, _, Action::Pressed, _) if pressed == key));
+    }
+
+    pub fn was_mouse_button_pressed(&self, button: MouseButton) -> bool {
+        return self.events.iter().any(|timed| matches!(timed.event, InputEvent::MouseButton(pressed, Action::Pressed) if pressed == button));
+    }
+
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+//                                             Action                                             //
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum Action {
+    Pressed,
+    Down,
+    Released,
+    Up
+}
+
+impl Action {
+
+    // Repeats are reported as InputEvent::KeyRepeat, the key stays down
+    pub(crate) fn from_glfw(action: glfw::Action) -> Option<Action> {
+        return match action {
+            glfw::Action::Press => Some(Action::Pressed),
+            glfw::Action::Release => Some(Action::Released),
+            glfw::Action::Repeat => None,
+        }
+    }
+
+    pub fn is_pressed(&self) -> bool {
+        matches!(self, Action::Pressed)
+    }
+
+    pub fn is_down(&self) -> bool {
+        matches!(self, Action::Down)
+    }
+
+    pub fn is_released(&self) -> bool {
+        matches!(self, Action::Released)
+    }
+
+    pub fn is_up(&self) -> bool {
+        matches!(self, Action::Up)
+    }
+
+    pub fn is_pressed_or_down(&self) -> bool {
+        matches!(self, Action::Pressed | Action::Down)
+    }
+
+    pub fn is_pressed_or_released(&self) -> bool {
+        matches!(self, Action::Pressed | Action::Released)
+    }
+
+    pub fn is_pressed_or_up(&self) -> bool {
+        matches!(self, Action::Pressed | Action::Up)
+    }
+
+    pub fn is_down_or_released(&self) -> bool {
+        matches!(self, Action::Down | Action::Released)
+    }
+
+    pub fn is_down_or_up(&self) -> bool {
+        matches!(self, Action::Down | Action::Up)
+    }
+
+    pub fn is_released_or_up(&self) -> bool {
+        matches!(self, Action::Released | Action::Up)
+    }
+
+}
+
+impl Default for Action {
+
+    fn default() -> Self {
+        return Action::Up;
+    }
+
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+//                                          Input Event                                           //
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+// Published on the event bus, only carries Action::Pressed and Action::Released. Keys unknown to
+// GLFW are reported as Key::Unknown with their scancode, scancodes are -1 when unknown
+#[derive(Clone, PartialEq, Debug)]
+pub enum InputEvent {
+    Key(Key, Scancode, Action, Modifiers),
+    KeyRepeat(Key, Scancode, Modifiers),
+    MouseButton(MouseButton, Action),
+    CursorMoved(f64, f64),
+    CursorEntered(bool),
+    Scroll(f64, f64),
+    Char(char),
+    FileDropped(PathBuf),
+    Gamepad(GamepadEvent),
+}
+
+// Input event along with the time it was received at, in seconds, see Input::get_time
+#[derive(Clone, PartialEq, Debug)]
+pub struct TimedInputEvent {
+    pub time: f64,
+    pub event: InputEvent,
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+//                                             Mouse                                              //
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+pub struct Mouse {
+    pub (super) x: f64,
+    pub (super) y: f64,
+    pub (super) last_x: f64,
+    pub (super) last_y: f64,
+    pub (super) cursor_in: bool,
+    pub (super) scroll_x: f64,
+    pub (super) scroll_y: f64,
+    pub (super) buttons: [Action; (glfw::ffi::MOUSE_BUTTON_LAST + 1) as usize],
+    // Sum of the moves of the frame, unaffected by the cursor leaving the window
+    motion_x: f64,
+    motion_y: f64,
+    // The first position only sets the origin of the motion
+    has_position: bool,
+    sensitivity: f64,
+    invert_x: bool,
+    invert_y: bool,
+}
+
+impl Mouse {
+
+    fn new() -> Mouse {
+        return Mouse {
+            x: 0.0,
+            y: 0.0,
+            last_x: 0.0,
+            last_y: 0.0,
+            cursor_in: false,
+            scroll_x: 0.0,
+            scroll_y: 0.0,
+            buttons: [Action::default(); (glfw::ffi::MOUSE_BUTTON_LAST + 1) as usize],
+            motion_x: 0.0,
+            motion_y: 0.0,
+            has_position: false,
+            sensitivity: 1.0,
+            invert_x: false,
+            invert_y: false,
+        }
+    }
+
+    fn update(&mut self) {
+        self.last_x = self.x;
+        self.last_y = self.y;
+        self.scroll_x = 0.0;
+        self.scroll_y = 0.0;
+        self.motion_x = 0.0;
+        self.motion_y = 0.0;
+
+        for button in self.buttons.iter_mut() {
+            match button {
+                Action::Pressed => *button = Action::Down,
+                Action::Released => *button = Action::Up,
+                _ => {}
+            }
+        }
+    }
+
+    // The next position only sets the origin of the motion
+    pub(crate) fn reset_motion(&mut self) {
+        self.has_position = false;
+    }
+
+    fn move_to(&mut self, x: f64, y: f64) {
+        if self.has_position {
+            self.motion_x += x - self.x;
+            self.motion_y += y - self.y;
+        }
+        self.x = x;
+        self.y = y;
+        self.has_position = true;
+    }
+
+    /* ==================================== Sensitivity ===================================== */
+
+    pub fn get_sensitivity(&self) -> f64 {
+        return self.sensitivity;
+    }
+
+    // Multiplies the motion, negative values are clamped to 0
+    pub fn set_sensitivity(&mut self, sensitivity: f64) {
+        self.sensitivity = sensitivity.max(0.0);
+    }
+
+    pub fn is_invert_x(&self) -> bool {
+        return self.invert_x;
+    }
+
+    pub fn set_invert_x(&mut self, invert_x: bool) {
+        self.invert_x = invert_x;
+    }
+
+    pub fn is_invert_y(&self) -> bool {
+        return self.invert_y;
+    }
+
+    pub fn set_invert_y(&mut self, invert_y: bool) {
+        self.invert_y = invert_y;
+    }
+
+    /* ====================================== Getters ======================================= */
+
+    pub fn get_x(&self) -> f64 {
+        return self.x;
+    }
+
+    pub fn get_y(&self) -> f64 {
+        return self.y;
+    }
+
+    pub fn get_last_x(&self) -> f64 {
+        return self.last_x;
+    }
+
+    pub fn get_last_y(&self) -> f64 {
+        return self.last_y;
+    }
+
+    pub fn get_dx(&self) -> f64 {
+        return self.x - self.last_x;
+    }
+
+    pub fn get_dy(&self) -> f64 {
+        return self.y - self.last_y;
+    }
+
+    // Motion of the frame scaled by the sensitivity and inverted if requested, used to turn a
+    // camera while the mouse is captured
+    pub fn get_motion_x(&self) -> f64 {
+        let motion = self.motion_x * self.sensitivity;
+        return if self.invert_x { -motion } else { motion };
+    }
+
+    pub fn get_motion_y(&self) -> f64 {
+        let motion = self.motion_y * self.sensitivity;
+        return if self.invert_y { -motion } else { motion };
+    }
+
+    pub fn get_raw_motion_x(&self) -> f64 {
+        return self.motion_x;
+    }
+
+    pub fn get_raw_motion_y(&self) -> f64 {
+        return self.motion_y;
+    }
+
+    pub fn is_cursor_in(&self) -> bool {
+        return self.cursor_in;
+    }
+
+    // Sum of every scroll event of the frame
+    pub fn get_scroll_x(&self) -> f64 {
+        return self.scroll_x;
+    }
+
+    pub fn is_scroll_left(&self) -> bool {
+        return self.scroll_x > 0.0;
+    }
+
+    pub fn is_scroll_right(&self) -> bool {
+        return self.scroll_x < 0.0;
+    }
+
+    pub fn is_scroll_x(&self) -> bool {
+        return self.scroll_x != 0.0;
+    }
+
+    pub fn get_scroll_y(&self) -> f64 {
+        return self.scroll_y;
+    }
+
+    pub fn is_scroll_up(&self) -> bool {
+        return self.scroll_y > 0.0;
+    }
+
+    pub fn is_scroll_down(&self) -> bool {
+        return self.scroll_y < 0.0;
+    }
+
+    pub fn is_scroll_y(&self) -> bool {
+        return self.scroll_y != 0.0;
+    }
+
+    pub fn get_button(&self, button: MouseButton) -> Action {
+        return self.buttons[button as usize];
+    }
+
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+//                                            Keyboard                                            //
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+pub struct Keyboard {
+    pub (super) keys: [Action; (glfw::ffi::KEY_LAST + 1) as usize],
+    // Physical keys, independent from the keyboard layout
+    pub (super) scancodes: HashMap<Scancode, Action>,
+    pub (super) repeats: Vec<Key>,
+    pub (super) lock_modifiers: Modifiers,
+    pub (super) chars: VecDeque<char>,
+    glfw: Option<Glfw>,
+}
+
+impl Keyboard {
+
+    pub fn new() -> Keyboard {
+        return Keyboard {
+            keys: [Action::default(); (glfw::ffi::KEY_LAST + 1) as usize],
+            scancodes: HashMap::new(),
+            repeats: Vec::new(),
+            lock_modifiers: Modifiers::empty(),
+            chars: VecDeque::new(),
+            glfw: None,
+        }
+    }
+
+    pub(super) fn update(&mut self) {
+        self.chars.clear();
+        self.repeats.clear();
+        for key in self.keys.iter_mut() {
+            match key {
+                Action::Pressed => *key = Action::Down,
+                Action::Released => *key = Action::Up,
+                _ => {}
+            }
+        }
+
+        self.scancodes.retain(|_, action| !action.is_released());
+        for action in self.scancodes.values_mut() {
+            *action = Action::Down;
+        }
+    }
+
+    fn set_key(&mut self, key: Key, scancode: Scancode, action: Action, modifiers: Modifiers) {
+        if key != Key::Unknown {
+            self.keys[key as usize] = action;
+        }
+        if scancode >= 0 {
+            self.scancodes.insert(scancode, action);
+        }
+        self.lock_modifiers = modifiers & (Modifiers::CapsLock | Modifiers::NumLock);
+    }
+
+    /* ===================================== Modifiers ====================================== */
+
+    // Shift, Control, Alt and Super follow the keys currently held down, Caps Lock and Num Lock the
+    // state reported by the last key event
+    pub fn get_modifiers(&self) -> Modifiers {
+        let mut modifiers = self.lock_modifiers;
+        let held = |left: Key, right: Key| self.get_key(left).is_pressed_or_down() || self.get_key(right).is_pressed_or_down();
+        modifiers.set(Modifiers::Shift, held(Key::LeftShift, Key::RightShift));
+        modifiers.set(Modifiers::Control, held(Key::LeftControl, Key::RightControl));
+        modifiers.set(Modifiers::Alt, held(Key::LeftAlt, Key::RightAlt));
+        modifiers.set(Modifiers::Super, held(Key::LeftSuper, Key::RightSuper));
+        return modifiers;
+    }
+
+    pub fn is_shift_down(&self) -> bool {
+        return self.get_modifiers().contains(Modifiers::Shift);
+    }
+
+    pub fn is_control_down(&self) -> bool {
+        return self.get_modifiers().contains(Modifiers::Control);
+    }
+
+    pub fn is_alt_down(&self) -> bool {
+        return self.get_modifiers().contains(Modifiers::Alt);
+    }
+
+    pub fn is_super_down(&self) -> bool {
+        return self.get_modifiers().contains(Modifiers::Super);
+    }
+
+    pub fn is_caps_lock_on(&self) -> bool {
+        return self.lock_modifiers.contains(Modifiers::CapsLock);
+    }
+
+    pub fn is_num_lock_on(&self) -> bool {
+        return self.lock_modifiers.contains(Modifiers::NumLock);
+    }
+
+    /* ====================================== Repeats ======================================= */
+
+    // Repeats sent by the system while the key is held down, used for text editing
+    pub fn is_repeated(&self, key: Key) -> bool {
+        return self.repeats.contains(&key);
+    }
+
+    pub fn is_pressed_or_repeated(&self, key: Key) -> bool {
+        return self.get_key(key).is_pressed() || self.is_repeated(key);
+    }
+
+    pub fn get_repeats(&self) -> &[Key] {
+        return &self.repeats;
+    }
+
+    /* ===================================== Scancodes ====================================== */
+
+    // Platform specific code of the physical key, None without a window
+    pub fn get_scancode(&self, key: Key) -> Option<Scancode> {
+        self.glfw.as_ref()?;
+        return key.get_scancode().filter(|scancode| *scancode >= 0);
+    }
+
+    pub fn get_scancode_action(&self, scancode: Scancode) -> Action {
+        return self.scancodes.get(&scancode).copied().unwrap_or_default();
+    }
+
+    // State of the physical key found at the position of the given key on a US keyboard, so
+    // Key::W stays under the same finger on any layout
+    pub fn get_physical_key(&self, key: Key) -> Action {
+        return match self.get_scancode(key) {
+            Some(scancode) => self.get_scancode_action(scancode),
+            None => self.get_key(key),
+        }
+    }
+
+    /* ======================================= Names ======================================== */
+
+    // Name to display in rebinding menus: printable keys follow the keyboard layout ("Z" for
+    // Key::W on an AZERTY keyboard), other keys use their label ("Left Shift")
+    pub fn get_key_name(&self, key: Key) -> String {
+        if self.glfw.is_some() {
+            if let Some(name) = key.get_name() {
+                return name.to_uppercase();
+            }
+        }
+        return key_label(key);
+    }
+
+    pub fn get_scancode_name(&self, scancode: Scancode) -> Option<String> {
+        self.glfw.as_ref()?;
+        return glfw::get_key_name(None, Some(scancode)).map(|name| name.to_uppercase());
+    }
+
+    /* ====================================== Getters ======================================= */
+
+    pub fn get_key(&self, key: Key) -> Action {
+        if key == Key::Unknown {
+            return Action::Up;
+        }
+        return self.keys[key as usize];
+    }
+
+    // Characters typed this frame, in order
+    pub fn get_chars(&self) -> VecDeque<char> {
+        return self.chars.clone();
+    }
+
+    pub fn get_text(&self) -> String {
+        return self.chars.iter().collect();
+    }
+
+    // Ctrl+C, Ctrl+X or Ctrl+V pressed this frame, Cmd replaces Ctrl on macOS
+    pub fn get_clipboard_action(&self) -> Option<ClipboardAction> {
+        let modifiers = if cfg!(target_os = "macos") {
+            [Key::LeftSuper, Key::RightSuper]
+        } else {
+            [Key::LeftControl, Key::RightControl]
+        };
+        if !modifiers.iter().any(|key| self.get_key(*key).is_pressed_or_down()) {
+            return None;
+        }
+
+        if self.get_key(Key::C).is_pressed() {
+            return Some(ClipboardAction::Copy);
+        } else if self.get_key(Key::X).is_pressed() {
+            return Some(ClipboardAction::Cut);
+        } else if self.get_key(Key::V).is_pressed() {
+            return Some(ClipboardAction::Paste);
+        }
+        return None;
+    }
+
+}
+
+impl Default for Keyboard {
+
+    fn default() -> Self {
+        return Keyboard::new();
+    }
+
+}
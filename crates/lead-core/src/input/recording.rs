@@ -0,0 +1,430 @@
+use crate::input::gamepad::{GamepadEvent, JoystickId};
+use crate::input::keys::{gamepad_axis_from_name, gamepad_axis_name, gamepad_button_from_name, gamepad_button_name, key_from_name, key_name, modifiers_from_name, modifiers_name, mouse_button_from_name, mouse_button_name};
+use crate::input::{Action, InputEvent, Modifiers};
+use glfw::Key;
+use std::collections::VecDeque;
+use std::fmt::{Display, Formatter};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+const HEADER: &str = "# lead-engine input recording v1";
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+//                                        Recording Error                                         //
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+#[derive(Debug)]
+pub enum RecordingError {
+    Io {
+        path: PathBuf,
+        error: std::io::Error,
+    },
+    Parse {
+        path: PathBuf,
+        line: usize,
+        message: String,
+    },
+}
+
+impl Display for RecordingError {
+
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        return match self {
+            RecordingError::Io { path, error } => write!(f, "Failed to access '{}': {}", path.display(), error),
+            RecordingError::Parse { path, line, message } => write!(f, "Failed to parse '{}' at line {}: {}", path.display(), line, message),
+        }
+    }
+
+}
+
+impl std::error::Error for RecordingError {}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+//                                         Recorded Frame                                         //
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct RecordedFrame {
+    delta: f64,
+    events: Vec<InputEvent>,
+}
+
+impl RecordedFrame {
+
+    pub fn new(delta: f64, events: Vec<InputEvent>) -> RecordedFrame {
+        return RecordedFrame {
+            delta,
+            events,
+        }
+    }
+
+    /* ====================================== Getters ======================================= */
+
+    pub fn get_delta(&self) -> f64 {
+        return self.delta;
+    }
+
+    pub fn get_events(&self) -> &[InputEvent] {
+        return &self.events;
+    }
+
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+//                                        Input Recording                                         //
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+// Every input event of a session grouped by frame, along with the delta of each frame.
+// Stored as text, one event per line below a "frame <delta>" line
+#[derive(Clone, Default, PartialEq, Debug)]
+pub struct InputRecording {
+    frames: Vec<RecordedFrame>,
+}
+
+impl InputRecording {
+
+    pub fn new() -> InputRecording {
+        return InputRecording {
+            frames: Vec::new(),
+        }
+    }
+
+    pub fn push_frame(&mut self, delta: f64, events: Vec<InputEvent>) {
+        self.frames.push(RecordedFrame::new(delta, events));
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<InputRecording, RecordingError> {
+        let path = path.as_ref();
+        let source = std::fs::read_to_string(path).map_err(|error| RecordingError::Io { path: path.to_path_buf(), error })?;
+
+        let mut recording = InputRecording::new();
+        for (index, line) in source.lines().enumerate() {
+            let error = |message: String| RecordingError::Parse { path: path.to_path_buf(), line: index + 1, message };
+
+            let line = line.trim_end();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            if let Some(delta) = line.strip_prefix("frame ") {
+                let delta = delta.parse::<f64>().map_err(|_| error(format!("Invalid frame delta '{}'", delta)))?;
+                recording.push_frame(delta, Vec::new());
+                continue;
+            }
+
+            let event = parse_event(line).map_err(error)?;
+            match recording.frames.last_mut() {
+                Some(frame) => frame.events.push(event),
+                None => return Err(error("Event outside of a frame".to_string())),
+            }
+        }
+        return Ok(recording);
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), RecordingError> {
+        let path = path.as_ref();
+        let mut output = String::new();
+        output.push_str(HEADER);
+        output.push('\n');
+        for frame in self.frames.iter() {
+            output.push_str(&format!("frame {}\n", frame.delta));
+            for event in frame.events.iter() {
+                output.push_str(&format_event(event));
+                output.push('\n');
+            }
+        }
+
+        let io_error = |error| RecordingError::Io { path: path.to_path_buf(), error };
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(io_error)?;
+        }
+        return std::fs::write(path, output).map_err(io_error);
+    }
+
+    /* ====================================== Getters ======================================= */
+
+    pub fn get_frames(&self) -> &[RecordedFrame] {
+        return &self.frames;
+    }
+
+    pub fn len(&self) -> usize {
+        return self.frames.len();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        return self.frames.is_empty();
+    }
+
+    // Total time covered by the recording, in seconds
+    pub fn get_duration(&self) -> f64 {
+        return self.frames.iter().map(|frame| frame.delta).sum();
+    }
+
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+//                                             Replay                                             //
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+pub(crate) struct Replay {
+    frames: VecDeque<RecordedFrame>,
+}
+
+impl Replay {
+
+    pub(crate) fn new(recording: InputRecording) -> Replay {
+        return Replay {
+            frames: recording.frames.into(),
+        }
+    }
+
+    pub(crate) fn next_frame(&mut self) -> Option<RecordedFrame> {
+        return self.frames.pop_front();
+    }
+
+    pub(crate) fn is_finished(&self) -> bool {
+        return self.frames.is_empty();
+    }
+
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+//                                             Format                                             //
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+fn format_action(action: Action) -> &'static str {
+    return if action.is_pressed_or_down() { "pressed" } else { "released" };
+}
+
+fn parse_action(name: &str) -> Result<Action, String> {
+    return match name {
+        "pressed" => Ok(Action::Pressed),
+        "released" => Ok(Action::Released),
+        _ => Err(format!("Invalid action '{}'", name)),
+    }
+}
+
+fn format_modifiers(modifiers: Modifiers) -> String {
+    return if modifiers.is_empty() { "-".to_string() } else { modifiers_name(modifiers) };
+}
+
+fn parse_modifiers(name: &str) -> Result<Modifiers, String> {
+    if name == "-" {
+        return Ok(Modifiers::empty());
+    }
+    return modifiers_from_name(name).ok_or_else(|| format!("Invalid modifiers '{}'", name));
+}
+
+fn parse_key(name: &str) -> Result<Key, String> {
+    if name == "unknown" {
+        return Ok(Key::Unknown);
+    }
+    return key_from_name(name).ok_or_else(|| format!("Invalid key '{}'", name));
+}
+
+fn parse_value<T: FromStr>(value: Option<&str>) -> Result<T, String> {
+    let value = value.ok_or_else(|| "Missing value".to_string())?;
+    return value.parse::<T>().map_err(|_| format!("Invalid value '{}'", value));
+}
+
+fn parse_joystick(value: Option<&str>) -> Result<JoystickId, String> {
+    let id = parse_value::<i32>(value)?;
+    return JoystickId::from_i32(id).ok_or_else(|| format!("Invalid joystick '{}'", id));
+}
+
+// Floats use their shortest representation which parses back to the exact same value
+fn format_event(event: &InputEvent) -> String {
+    return match event {
+        InputEvent::Key(key, scancode, action, modifiers) => {
+            format!("key {} {} {} {}", key_name(*key), scancode, format_action(*action), format_modifiers(*modifiers))
+        }
+        InputEvent::KeyRepeat(key, scancode, modifiers) => {
+            format!("repeat {} {} {}", key_name(*key), scancode, format_modifiers(*modifiers))
+        }
+        InputEvent::MouseButton(button, action) => format!("mouse {} {}", mouse_button_name(*button), format_action(*action)),
+        InputEvent::CursorMoved(x, y) => format!("cursor {} {}", x, y),
+        InputEvent::CursorEntered(cursor_in) => format!("enter {}", cursor_in),
+        InputEvent::Scroll(x, y) => format!("scroll {} {}", x, y),
+        InputEvent::Char(chr) => format!("char {}", *chr as u32),
+        InputEvent::FileDropped(path) => format!("drop {}", path.display()),
+        InputEvent::Gamepad(GamepadEvent::Connected { id, name, guid, mapped }) => {
+            let guid = if guid.is_empty() { "-" } else { guid };
+            format!("gamepad-connected {} {} {} {}", *id as i32, mapped, guid, name)
+        }
+        InputEvent::Gamepad(GamepadEvent::Disconnected(id)) => format!("gamepad-disconnected {}", *id as i32),
+        InputEvent::Gamepad(GamepadEvent::Button(id, button, action)) => {
+            format!("gamepad-button {} {} {}", *id as i32, gamepad_button_name(*button), format_action(*action))
+        }
+        InputEvent::Gamepad(GamepadEvent::Axis(id, axis, value)) => {
+            format!("gamepad-axis {} {} {}", *id as i32, gamepad_axis_name(*axis), value)
+        }
+    }
+}
+
+fn parse_event(line: &str) -> Result<InputEvent, String> {
+    let (kind, rest) = line.split_once(' ').unwrap_or((line, ""));
+
+    // Paths and gamepad names may contain spaces
+    if kind == "drop" {
+        return Ok(InputEvent::FileDropped(PathBuf::from(rest)));
+    }
+    if kind == "gamepad-connected" {
+        let mut args = rest.splitn(4, ' ');
+        let id = parse_joystick(args.next())?;
+        let mapped = parse_value::<bool>(args.next())?;
+        let guid = args.next().ok_or_else(|| "Missing guid".to_string())?;
+        return Ok(InputEvent::Gamepad(GamepadEvent::Connected {
+            id,
+            name: args.next().unwrap_or("").to_string(),
+            guid: if guid == "-" { String::new() } else { guid.to_string() },
+            mapped,
+        }));
+    }
+
+    let mut args = rest.split_whitespace();
+    let event = match kind {
+        "key" => {
+            let key = parse_key(args.next().unwrap_or(""))?;
+            let scancode = parse_value(args.next())?;
+            let action = parse_action(args.next().unwrap_or(""))?;
+            InputEvent::Key(key, scancode, action, parse_modifiers(args.next().unwrap_or(""))?)
+        }
+        "repeat" => {
+            let key = parse_key(args.next().unwrap_or(""))?;
+            let scancode = parse_value(args.next())?;
+            InputEvent::KeyRepeat(key, scancode, parse_modifiers(args.next().unwrap_or(""))?)
+        }
+        "mouse" => {
+            let name = args.next().unwrap_or("");
+            let button = mouse_button_from_name(name).ok_or_else(|| format!("Invalid mouse button '{}'", name))?;
+            InputEvent::MouseButton(button, parse_action(args.next().unwrap_or(""))?)
+        }
+        "cursor" => InputEvent::CursorMoved(parse_value(args.next())?, parse_value(args.next())?),
+        "enter" => InputEvent::CursorEntered(parse_value(args.next())?),
+        "scroll" => InputEvent::Scroll(parse_value(args.next())?, parse_value(args.next())?),
+        "char" => {
+            let code = parse_value::<u32>(args.next())?;
+            InputEvent::Char(char::from_u32(code).ok_or_else(|| format!("Invalid character '{}'", code))?)
+        }
+        "gamepad-disconnected" => InputEvent::Gamepad(GamepadEvent::Disconnected(parse_joystick(args.next())?)),
+        "gamepad-button" => {
+            let id = parse_joystick(args.next())?;
+            let name = args.next().unwrap_or("");
+            let button = gamepad_button_from_name(name).ok_or_else(|| format!("Invalid gamepad button '{}'", name))?;
+            InputEvent::Gamepad(GamepadEvent::Button(id, button, parse_action(args.next().unwrap_or(""))?))
+        }
+        "gamepad-axis" => {
+            let id = parse_joystick(args.next())?;
+            let name = args.next().unwrap_or("");
+            let axis = gamepad_axis_from_name(name).ok_or_else(|| format!("Invalid gamepad axis '{}'", name))?;
+            InputEvent::Gamepad(GamepadEvent::Axis(id, axis, parse_value(args.next())?))
+        }
+        _ => return Err(format!("Unknown event '{}'", kind)),
+    };
+
+    if let Some(extra) = args.next() {
+        return Err(format!("Unexpected '{}'", extra));
+    }
+    return Ok(event);
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+//                                             Tests                                              //
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::gamepad::{GamepadAxis, GamepadButton};
+    use glfw::MouseButton;
+
+    fn temp_path(name: &str) -> PathBuf {
+        return std::env::temp_dir().join(format!("lead-recording-{}", std::process::id())).join(name);
+    }
+
+    fn load_source(name: &str, source: &str) -> Result<InputRecording, RecordingError> {
+        let path = temp_path(name);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, source).unwrap();
+        return InputRecording::load(path);
+    }
+
+    #[test]
+    fn recordings_round_trip_through_files() {
+        let mut recording = InputRecording::new();
+        recording.push_frame(1.0 / 60.0, vec![
+            InputEvent::Key(Key::W, 17, Action::Pressed, Modifiers::Shift | Modifiers::Control),
+            InputEvent::KeyRepeat(Key::W, 17, Modifiers::empty()),
+            InputEvent::Key(Key::Unknown, 0, Action::Released, Modifiers::empty()),
+            InputEvent::Char('é'),
+            InputEvent::Char(' '),
+        ]);
+        recording.push_frame(0.1, Vec::new());
+        recording.push_frame(0.016, vec![
+            InputEvent::MouseButton(MouseButton::Button1, Action::Pressed),
+            InputEvent::CursorMoved(0.1 + 0.2, -3.5),
+            InputEvent::CursorEntered(false),
+            InputEvent::Scroll(0.0, -1.25),
+            InputEvent::FileDropped(PathBuf::from("/tmp/some file.png")),
+            InputEvent::Gamepad(GamepadEvent::Connected { id: JoystickId::Joystick2, name: "Xbox Controller".to_string(), guid: String::new(), mapped: true }),
+            InputEvent::Gamepad(GamepadEvent::Button(JoystickId::Joystick2, GamepadButton::ButtonLeftBumper, Action::Released)),
+            InputEvent::Gamepad(GamepadEvent::Axis(JoystickId::Joystick2, GamepadAxis::AxisLeftX, -0.333)),
+            InputEvent::Gamepad(GamepadEvent::Disconnected(JoystickId::Joystick2)),
+        ]);
+
+        let path = temp_path("round-trip").join("recording.txt");
+        recording.save(&path).unwrap();
+        let loaded = InputRecording::load(&path).unwrap();
+        assert_eq!(loaded, recording);
+        assert_eq!(loaded.len(), 3);
+        assert_eq!(loaded.get_duration(), recording.get_duration());
+        assert!(std::fs::read_to_string(&path).unwrap().starts_with(HEADER));
+    }
+
+    #[test]
+    fn comments_and_blank_lines_are_skipped() {
+        let recording = load_source("comments.txt", "# comment\n\nframe 0.5\n  \nkey space 57 pressed -\n# another\nframe 0.25\n").unwrap();
+        assert_eq!(recording.get_frames(), [
+            RecordedFrame::new(0.5, vec![InputEvent::Key(Key::Space, 57, Action::Pressed, Modifiers::empty())]),
+            RecordedFrame::new(0.25, Vec::new()),
+        ]);
+    }
+
+    #[test]
+    fn parse_errors_report_their_line() {
+        let cases = [
+            ("outside.txt", "key a 0 pressed -\n", 1, "Event outside of a frame"),
+            ("delta.txt", "frame fast\n", 1, "Invalid frame delta 'fast'"),
+            ("kind.txt", "frame 0.1\n\ntouch 1 2\n", 3, "Unknown event 'touch'"),
+            ("key.txt", "frame 0.1\nkey enterr 0 pressed -\n", 2, "Invalid key 'enterr'"),
+            ("action.txt", "frame 0.1\nmouse left held\n", 2, "Invalid action 'held'"),
+            ("missing.txt", "frame 0.1\ncursor 1\n", 2, "Missing value"),
+            ("extra.txt", "frame 0.1\nscroll 1 2 3\n", 2, "Unexpected '3'"),
+            ("joystick.txt", "frame 0.1\ngamepad-disconnected 42\n", 2, "Invalid joystick '42'"),
+        ];
+        for (name, source, expected_line, expected_message) in cases {
+            match load_source(name, source) {
+                Err(RecordingError::Parse { line, message, .. }) => {
+                    assert_eq!(line, expected_line, "{}", name);
+                    assert_eq!(message, expected_message, "{}", name);
+                }
+                result => panic!("{}: expected a parse error, got {:?}", name, result),
+            }
+        }
+    }
+
+    #[test]
+    fn replays_hand_out_frames_in_order() {
+        let mut recording = InputRecording::new();
+        recording.push_frame(0.1, vec![InputEvent::Char('a')]);
+        recording.push_frame(0.2, Vec::new());
+
+        let mut replay = Replay::new(recording);
+        assert_eq!(replay.next_frame().map(|frame| frame.get_delta()), Some(0.1));
+        assert!(!replay.is_finished());
+        assert_eq!(replay.next_frame().map(|frame| frame.get_delta()), Some(0.2));
+        assert!(replay.is_finished());
+        assert!(replay.next_frame().is_none());
+    }
+
+}
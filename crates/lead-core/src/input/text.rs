@@ -0,0 +1,584 @@
+use crate::clipboard::Clipboard;
+use crate::input::{Action, Input, InputEvent, Key, Modifiers};
+
+// Undo steps kept per buffer, older steps are dropped
+pub const MAX_UNDO: usize = 100;
+
+fn is_word_char(chr: char) -> bool {
+    return chr.is_alphanumeric() || chr == '_';
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+//                                          Text Buffer                                           //
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+enum Edit {
+    Typing,
+    Deleting,
+    Other,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+struct Snapshot {
+    text: String,
+    cursor: usize,
+    anchor: Option<usize>,
+}
+
+// Editable line or block of text, positions are in characters. The selection spans from the anchor
+// to the cursor, in either direction
+#[derive(Clone, Debug)]
+pub struct TextBuffer {
+    text: String,
+    cursor: usize,
+    anchor: Option<usize>,
+    // Text being composed by an input method, shown at the cursor but not part of the text yet.
+    // GLFW does not report compositions, they have to be set by the platform layer
+    composition: Option<String>,
+    multiline: bool,
+    max_length: Option<usize>,
+    undo: Vec<Snapshot>,
+    redo: Vec<Snapshot>,
+    // Consecutive characters typed or deleted are undone at once
+    last_edit: Option<Edit>,
+}
+
+impl TextBuffer {
+
+    pub fn new() -> TextBuffer {
+        return TextBuffer {
+            text: String::new(),
+            cursor: 0,
+            anchor: None,
+            composition: None,
+            multiline: false,
+            max_length: None,
+            undo: Vec::new(),
+            redo: Vec::new(),
+            last_edit: None,
+        }
+    }
+
+    pub fn with_text(text: &str) -> TextBuffer {
+        let mut buffer = TextBuffer::new();
+        buffer.set_text(text);
+        return buffer;
+    }
+
+    // Applies the keys and characters of the frame in the order they were received, returns whether
+    // the text changed. Ctrl+C, Ctrl+X and Ctrl+V go through the clipboard, see Context::edit_text
+    pub fn update(&mut self, input: &Input, clipboard: &mut dyn Clipboard) -> bool {
+        let before = self.text.clone();
+        for timed in input.get_events() {
+            match &timed.event {
+                // Characters typed with AltGr arrive with Ctrl and Alt held, shortcuts produce none
+                InputEvent::Char(chr) => self.type_text(chr.encode_utf8(&mut [0; 4])),
+                InputEvent::Key(key, _, Action::Pressed, modifiers) | InputEvent::KeyRepeat(key, _, modifiers) => {
+                    self.handle_key(*key, *modifiers, clipboard);
+                }
+                _ => {}
+            }
+        }
+        return self.text != before;
+    }
+
+    // Modifiers are the ones held when the key was pressed
+    fn handle_key(&mut self, key: Key, modifiers: Modifiers, clipboard: &mut dyn Clipboard) {
+        let word = modifiers.contains(if cfg!(target_os = "macos") { Modifiers::Alt } else { Modifiers::Control });
+        let command = modifiers.contains(if cfg!(target_os = "macos") { Modifiers::Super } else { Modifiers::Control });
+        let select = modifiers.contains(Modifiers::Shift);
+
+        match key {
+            Key::Backspace if word => self.delete_word_backward(),
+            Key::Backspace => self.delete_backward(),
+            Key::Delete if word => self.delete_word_forward(),
+            Key::Delete => self.delete_forward(),
+            Key::Left if word => self.move_word_left(select),
+            Key::Left => self.move_left(select),
+            Key::Right if word => self.move_word_right(select),
+            Key::Right => self.move_right(select),
+            Key::Home => self.move_home(select),
+            Key::End => self.move_end(select),
+            Key::Enter | Key::KpEnter if self.multiline => self.type_text("\n"),
+            Key::A if command => self.select_all(),
+            Key::Z if command && select => self.redo(),
+            Key::Z if command => self.undo(),
+            Key::Y if command => self.redo(),
+            Key::C if command => self.copy(clipboard),
+            Key::X if command => self.cut(clipboard),
+            Key::V if command => self.paste(clipboard),
+            _ => {}
+        }
+    }
+
+    /* ====================================== Editing ======================================= */
+
+    // Replaces the selection, if any, with the text
+    pub fn insert(&mut self, text: &str) {
+        self.edit(Edit::Other, text);
+    }
+
+    fn type_text(&mut self, text: &str) {
+        self.edit(Edit::Typing, text);
+    }
+
+    fn edit(&mut self, kind: Edit, text: &str) {
+        let text: String = match self.multiline {
+            true => text.to_string(),
+            false => text.chars().filter(|chr| *chr != '\n' && *chr != '\r').collect(),
+        };
+
+        let (start, end) = self.get_selection().unwrap_or((self.cursor, self.cursor));
+        let available = self.max_length.map(|max| max.saturating_sub(self.len() - (end - start))).unwrap_or(usize::MAX);
+        let text: String = text.chars().take(available).collect();
+        if text.is_empty() && start == end {
+            return;
+        }
+
+        self.push_undo(kind);
+        self.replace_range(start, end, &text);
+        self.cursor = start + text.chars().count();
+        self.anchor = None;
+    }
+
+    pub fn delete_backward(&mut self) {
+        self.delete_to(self.cursor.saturating_sub(1));
+    }
+
+    pub fn delete_forward(&mut self) {
+        self.delete_to((self.cursor + 1).min(self.len()));
+    }
+
+    pub fn delete_word_backward(&mut self) {
+        self.delete_to(self.find_word_left(self.cursor));
+    }
+
+    pub fn delete_word_forward(&mut self) {
+        self.delete_to(self.find_word_right(self.cursor));
+    }
+
+    // Deletes the selection, or the text between the cursor and the position
+    fn delete_to(&mut self, position: usize) {
+        let (start, end) = match self.get_selection() {
+            Some(selection) => selection,
+            None => (self.cursor.min(position), self.cursor.max(position)),
+        };
+        if start == end {
+            return;
+        }
+
+        self.push_undo(Edit::Deleting);
+        self.replace_range(start, end, "");
+        self.cursor = start;
+        self.anchor = None;
+    }
+
+    pub fn clear(&mut self) {
+        self.select_all();
+        self.insert("");
+    }
+
+    fn replace_range(&mut self, start: usize, end: usize, text: &str) {
+        let (start, end) = (self.byte_index(start), self.byte_index(end));
+        self.text.replace_range(start..end, text);
+    }
+
+    /* ===================================== Navigation ===================================== */
+
+    // Moves the cursor, extending the selection from the previous cursor position when selecting
+    pub fn set_cursor(&mut self, position: usize, select: bool) {
+        let position = position.min(self.len());
+        if select {
+            self.anchor.get_or_insert(self.cursor);
+        } else {
+            self.anchor = None;
+        }
+        self.cursor = position;
+        if self.anchor == Some(position) {
+            self.anchor = None;
+        }
+        self.last_edit = None;
+    }
+
+    // Without selecting, a selection collapses to its start
+    pub fn move_left(&mut self, select: bool) {
+        match self.get_selection() {
+            Some((start, _)) if !select => self.set_cursor(start, false),
+            _ => self.set_cursor(self.cursor.saturating_sub(1), select),
+        }
+    }
+
+    pub fn move_right(&mut self, select: bool) {
+        match self.get_selection() {
+            Some((_, end)) if !select => self.set_cursor(end, false),
+            _ => self.set_cursor(self.cursor + 1, select),
+        }
+    }
+
+    pub fn move_word_left(&mut self, select: bool) {
+        self.set_cursor(self.find_word_left(self.cursor), select);
+    }
+
+    pub fn move_word_right(&mut self, select: bool) {
+        self.set_cursor(self.find_word_right(self.cursor), select);
+    }
+
+    // Start and end of the current line
+    pub fn move_home(&mut self, select: bool) {
+        let chars: Vec<char> = self.text.chars().collect();
+        let start = chars[..self.cursor].iter().rposition(|chr| *chr == '\n').map(|index| index + 1).unwrap_or(0);
+        self.set_cursor(start, select);
+    }
+
+    pub fn move_end(&mut self, select: bool) {
+        let chars: Vec<char> = self.text.chars().collect();
+        let end = chars[self.cursor..].iter().position(|chr| *chr == '\n').map(|index| self.cursor + index).unwrap_or(chars.len());
+        self.set_cursor(end, select);
+    }
+
+    pub fn select_all(&mut self) {
+        self.set_cursor(0, false);
+        self.set_cursor(self.len(), true);
+    }
+
+    pub fn clear_selection(&mut self) {
+        self.anchor = None;
+    }
+
+    // Start of the word before the position, skipping whitespace and punctuation first
+    fn find_word_left(&self, position: usize) -> usize {
+        let chars: Vec<char> = self.text.chars().collect();
+        let mut index = position;
+        while index > 0 && !is_word_char(chars[index - 1]) {
+            index -= 1;
+        }
+        while index > 0 && is_word_char(chars[index - 1]) {
+            index -= 1;
+        }
+        return index;
+    }
+
+    // End of the word after the position
+    fn find_word_right(&self, position: usize) -> usize {
+        let chars: Vec<char> = self.text.chars().collect();
+        let mut index = position;
+        while index < chars.len() && !is_word_char(chars[index]) {
+            index += 1;
+        }
+        while index < chars.len() && is_word_char(chars[index]) {
+            index += 1;
+        }
+        return index;
+    }
+
+    /* ======================================== Undo ======================================== */
+
+    fn push_undo(&mut self, kind: Edit) {
+        self.redo.clear();
+        if kind != Edit::Other && self.last_edit == Some(kind) {
+            return;
+        }
+
+        self.undo.push(self.snapshot());
+        if self.undo.len() > MAX_UNDO {
+            self.undo.remove(0);
+        }
+        self.last_edit = Some(kind);
+    }
+
+    pub fn undo(&mut self) {
+        if let Some(snapshot) = self.undo.pop() {
+            self.redo.push(self.snapshot());
+            self.restore(snapshot);
+        }
+    }
+
+    pub fn redo(&mut self) {
+        if let Some(snapshot) = self.redo.pop() {
+            self.undo.push(self.snapshot());
+            self.restore(snapshot);
+        }
+    }
+
+    pub fn can_undo(&self) -> bool {
+        return !self.undo.is_empty();
+    }
+
+    pub fn can_redo(&self) -> bool {
+        return !self.redo.is_empty();
+    }
+
+    fn snapshot(&self) -> Snapshot {
+        return Snapshot {
+            text: self.text.clone(),
+            cursor: self.cursor,
+            anchor: self.anchor,
+        }
+    }
+
+    fn restore(&mut self, snapshot: Snapshot) {
+        self.text = snapshot.text;
+        self.cursor = snapshot.cursor;
+        self.anchor = snapshot.anchor;
+        self.last_edit = None;
+    }
+
+    /* ===================================== Clipboard ====================================== */
+
+    pub fn copy(&self, clipboard: &mut dyn Clipboard) {
+        if let Some(text) = self.get_selected_text() {
+            clipboard.set_text(&text);
+        }
+    }
+
+    pub fn cut(&mut self, clipboard: &mut dyn Clipboard) {
+        if self.get_selection().is_some() {
+            self.copy(clipboard);
+            self.delete_to(self.cursor);
+        }
+    }
+
+    pub fn paste(&mut self, clipboard: &mut dyn Clipboard) {
+        if let Some(text) = clipboard.get_text() {
+            self.insert(&text);
+        }
+    }
+
+    /* ==================================== Composition ===================================== */
+
+    pub fn set_composition(&mut self, composition: Option<&str>) {
+        self.composition = composition.filter(|text| !text.is_empty()).map(str::to_string);
+    }
+
+    // Inserts the composed text once the input method confirmed it
+    pub fn commit_composition(&mut self) {
+        if let Some(composition) = self.composition.take() {
+            self.insert(&composition);
+        }
+    }
+
+    pub fn get_composition(&self) -> Option<&str> {
+        return self.composition.as_deref();
+    }
+
+    // The text with the composition inserted at the cursor, for display
+    pub fn get_display_text(&self) -> String {
+        let mut text = self.text.clone();
+        if let Some(composition) = &self.composition {
+            text.insert_str(self.byte_index(self.cursor), composition);
+        }
+        return text;
+    }
+
+    /* ====================================== Getters ======================================= */
+
+    pub fn get_text(&self) -> &str {
+        return &self.text;
+    }
+
+    // Starts a new undo history, use insert to replace the text as an edit which can be undone
+    pub fn set_text(&mut self, text: &str) {
+        self.select_all();
+        self.insert(text);
+        self.undo.clear();
+        self.redo.clear();
+        self.last_edit = None;
+    }
+
+    // Length in characters
+    pub fn len(&self) -> usize {
+        return self.text.chars().count();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        return self.text.is_empty();
+    }
+
+    pub fn get_cursor(&self) -> usize {
+        return self.cursor;
+    }
+
+    // Ordered start and end of the selection, None when nothing is selected
+    pub fn get_selection(&self) -> Option<(usize, usize)> {
+        return self.anchor.map(|anchor| (anchor.min(self.cursor), anchor.max(self.cursor)));
+    }
+
+    pub fn get_selected_text(&self) -> Option<String> {
+        let (start, end) = self.get_selection()?;
+        return Some(self.text.chars().skip(start).take(end - start).collect());
+    }
+
+    pub fn is_multiline(&self) -> bool {
+        return self.multiline;
+    }
+
+    // Line breaks are removed from the input of single line buffers
+    pub fn set_multiline(&mut self, multiline: bool) {
+        self.multiline = multiline;
+    }
+
+    pub fn get_max_length(&self) -> Option<usize> {
+        return self.max_length;
+    }
+
+    // Longer input is truncated, the current text is left as is
+    pub fn set_max_length(&mut self, max_length: Option<usize>) {
+        self.max_length = max_length;
+    }
+
+    fn byte_index(&self, position: usize) -> usize {
+        return self.text.char_indices().nth(position).map(|(index, _)| index).unwrap_or(self.text.len());
+    }
+
+}
+
+impl Default for TextBuffer {
+
+    fn default() -> Self {
+        return TextBuffer::new();
+    }
+
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+//                                             Tests                                              //
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clipboard::MemoryClipboard;
+
+    const COMMAND: Modifiers = if cfg!(target_os = "macos") { Modifiers::Super } else { Modifiers::Control };
+
+    fn press(key: Key, modifiers: Modifiers) -> InputEvent {
+        return InputEvent::Key(key, -1, Action::Pressed, modifiers);
+    }
+
+    fn chars(text: &str) -> Vec<InputEvent> {
+        return text.chars().map(InputEvent::Char).collect();
+    }
+
+    // Runs one frame with the given events
+    fn frame(buffer: &mut TextBuffer, clipboard: &mut MemoryClipboard, events: Vec<InputEvent>) -> bool {
+        let mut input = Input::new();
+        for event in events {
+            input.handle_event(0.0, event);
+        }
+        return buffer.update(&input, clipboard);
+    }
+
+    #[test]
+    fn editing_moves_the_cursor() {
+        let mut buffer = TextBuffer::with_text("hello world");
+        assert_eq!(buffer.get_cursor(), 11);
+
+        buffer.move_word_left(false);
+        buffer.delete_word_backward();
+        assert_eq!(buffer.get_text(), "world");
+        buffer.insert("big ");
+        assert_eq!(buffer.get_text(), "big world");
+        assert_eq!(buffer.get_cursor(), 4);
+
+        buffer.move_word_right(true);
+        assert_eq!(buffer.get_selected_text().as_deref(), Some("world"));
+        buffer.insert("é");
+        buffer.delete_backward();
+        buffer.insert("ü");
+        assert_eq!(buffer.get_text(), "big ü");
+        assert_eq!(buffer.get_cursor(), 5);
+    }
+
+    #[test]
+    fn single_lines_and_max_length() {
+        let mut buffer = TextBuffer::new();
+        buffer.set_max_length(Some(5));
+        buffer.insert("one\ntwo\r\nthree");
+        assert_eq!(buffer.get_text(), "onetw");
+
+        buffer.set_multiline(true);
+        buffer.select_all();
+        buffer.insert("a\nb");
+        buffer.move_home(false);
+        assert_eq!(buffer.get_cursor(), 2);
+    }
+
+    #[test]
+    fn typing_is_undone_at_once() {
+        let mut buffer = TextBuffer::new();
+        let mut clipboard = MemoryClipboard::new();
+        frame(&mut buffer, &mut clipboard, chars("abc"));
+        frame(&mut buffer, &mut clipboard, chars("def"));
+        frame(&mut buffer, &mut clipboard, vec![press(Key::Backspace, Modifiers::empty())]);
+        assert_eq!(buffer.get_text(), "abcde");
+
+        buffer.undo();
+        assert_eq!(buffer.get_text(), "abcdef");
+        buffer.undo();
+        assert_eq!(buffer.get_text(), "");
+        assert!(!buffer.can_undo());
+
+        buffer.redo();
+        buffer.redo();
+        assert_eq!(buffer.get_text(), "abcde");
+        assert!(!buffer.can_redo());
+    }
+
+    #[test]
+    fn set_text_starts_a_new_history() {
+        let mut buffer = TextBuffer::with_text("first");
+        assert!(!buffer.can_undo());
+
+        buffer.insert("!");
+        buffer.set_text("second");
+        assert_eq!(buffer.get_text(), "second");
+        assert!(!buffer.can_undo());
+        assert!(!buffer.can_redo());
+    }
+
+    #[test]
+    fn events_are_applied_in_order() {
+        let mut buffer = TextBuffer::new();
+        let mut clipboard = MemoryClipboard::new();
+        let backspace = press(Key::Backspace, Modifiers::empty());
+        let mut events = chars("ab");
+        events.push(backspace.clone());
+        events.extend(chars("cd"));
+        events.push(backspace);
+        events.push(InputEvent::KeyRepeat(Key::Backspace, -1, Modifiers::empty()));
+
+        assert!(frame(&mut buffer, &mut clipboard, events));
+        assert_eq!(buffer.get_text(), "a");
+
+        frame(&mut buffer, &mut clipboard, [chars("xyz"), vec![press(Key::Left, Modifiers::empty())], chars("!")].concat());
+        assert_eq!(buffer.get_text(), "axy!z");
+    }
+
+    #[test]
+    fn characters_typed_with_altgr_are_inserted() {
+        let mut buffer = TextBuffer::new();
+        let mut clipboard = MemoryClipboard::new();
+        let events = vec![press(Key::Q, Modifiers::Control | Modifiers::Alt), InputEvent::Char('@')];
+        frame(&mut buffer, &mut clipboard, events);
+        assert_eq!(buffer.get_text(), "@");
+    }
+
+    #[test]
+    fn shortcuts_use_the_clipboard() {
+        let mut buffer = TextBuffer::with_text("copy me");
+        let mut clipboard = MemoryClipboard::new();
+        frame(&mut buffer, &mut clipboard, vec![press(Key::A, COMMAND), press(Key::X, COMMAND)]);
+        assert_eq!(buffer.get_text(), "");
+        assert_eq!(clipboard.get_text().as_deref(), Some("copy me"));
+
+        frame(&mut buffer, &mut clipboard, vec![press(Key::V, COMMAND), press(Key::V, COMMAND)]);
+        assert_eq!(buffer.get_text(), "copy mecopy me");
+
+        frame(&mut buffer, &mut clipboard, vec![press(Key::Z, COMMAND)]);
+        assert_eq!(buffer.get_text(), "copy me");
+        frame(&mut buffer, &mut clipboard, vec![press(Key::Z, COMMAND | Modifiers::Shift)]);
+        assert_eq!(buffer.get_text(), "copy mecopy me");
+    }
+
+}
@@ -1,23 +1,31 @@
-use lead_logger::{critical, get_logger, LogLevel};
-use lead_mem::singleton_mut;
-use crate::window::{get_window, UpdateCap};
-use crate::state::get_state_manager;
-use std::time::Instant;
+use lead_logger::critical;
+#[cfg(feature = "globals")]
+use lead_logger::{get_logger, LogLevel};
 use glfw::Glfw;
+#[cfg(feature = "globals")]
+use lead_mem::{singleton_mut, resources::Resources, pointer::MutPointer};
+#[cfg(feature = "globals")]
+use crate::engine::Engine;
 
 ////////////////////////////////////////////////////////////////////////////////////////////////////
 //                                            Modules                                             //
 ////////////////////////////////////////////////////////////////////////////////////////////////////
 
+pub mod config;
+pub mod engine;
+pub mod event;
 pub mod window;
+pub mod monitor;
+pub mod image;
 pub mod input;
+pub mod clipboard;
 pub mod state;
 
 ////////////////////////////////////////////////////////////////////////////////////////////////////
 //                                              GLFW                                              //
 ////////////////////////////////////////////////////////////////////////////////////////////////////
 
-fn init_glfw() -> Glfw {
+pub(crate) fn init_glfw() -> Glfw {
     match glfw::init(glfw::FAIL_ON_ERRORS) {
         Ok(glfw) => glfw,
         Err(err) => {
@@ -26,43 +34,29 @@ fn init_glfw() -> Glfw {
     }
 }
 
-singleton_mut!(func: get_glfw, GLFW, Glfw, init_glfw());
-
 ////////////////////////////////////////////////////////////////////////////////////////////////////
-//                                             Start                                              //
+//                                            Globals                                             //
 ////////////////////////////////////////////////////////////////////////////////////////////////////
 
-pub fn start() {
-    get_logger().as_mut().set_level(LogLevel::Debug);
-
-    let window = get_window();
-    let state_manager = get_state_manager();
-
-    // Initialize
-    state_manager.as_mut().init();
-
-    // Loop
-    let mut last = Instant::now();
-    while !window.as_ref().should_close() {
-        let delta = last.elapsed().as_secs_f64();
-        let can_update = match window.as_ref().get_update_cap() {
-            UpdateCap::Cap(cap) => delta >= 1.0 / cap as f64,
-            _ => true
-        };
+#[cfg(feature = "globals")]
+singleton_mut!(func: get_engine, ENGINE, Engine, Engine::new());
 
-        if can_update {
-            // Window Update
-            window.as_mut().process_events();
+// Shares the glfw handle of the engine's primary window, a headless engine initializes its own
+#[cfg(feature = "globals")]
+singleton_mut!(func: get_glfw, GLFW, Glfw, get_engine().as_ref().context().window().get_glfw().unwrap_or_else(init_glfw));
 
-            // State update
-            state_manager.as_mut().update(delta);
+#[cfg(feature = "globals")]
+pub fn get_resources() -> MutPointer<Resources> {
+    return MutPointer::new(get_engine().as_mut().context_mut().resources_mut());
+}
 
-            // Swap Buffers
-            window.as_mut().swap_buffers();
-            last = Instant::now();
-        }
-    }
+////////////////////////////////////////////////////////////////////////////////////////////////////
+//                                             Start                                              //
+////////////////////////////////////////////////////////////////////////////////////////////////////
 
-    // Dispose
-    state_manager.as_mut().dispose();
+#[cfg(feature = "globals")]
+pub fn start() {
+    // Subsystems outside of the engine still log through the global logger
+    get_logger().as_mut().set_level(LogLevel::Debug);
+    get_engine().as_mut().run();
 }
@@ -0,0 +1,248 @@
+use glfw::Glfw;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+use serde::{Deserialize, Serialize};
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+//                                           Video Mode                                           //
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub struct VideoMode {
+    pub width: u32,
+    pub height: u32,
+    pub refresh_rate: u32,
+}
+
+impl From<glfw::VidMode> for VideoMode {
+
+    fn from(mode: glfw::VidMode) -> Self {
+        return VideoMode {
+            width: mode.width,
+            height: mode.height,
+            refresh_rate: mode.refresh_rate,
+        }
+    }
+
+}
+
+impl Display for VideoMode {
+
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        return write!(f, "{}x{}@{}", self.width, self.height, self.refresh_rate);
+    }
+
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+//                                     Fullscreen Resolution                                      //
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+// Video mode requested for exclusive fullscreen, written as "desktop", "1920x1080" or
+// "1920x1080@144" in the config
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default, Deserialize, Serialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum FullscreenResolution {
+    #[default]
+    Desktop,
+    Exact {
+        width: u32,
+        height: u32,
+        refresh_rate: Option<u32>,
+    },
+}
+
+impl FromStr for FullscreenResolution {
+
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let expected = || format!("expected \"desktop\", \"<width>x<height>\" or \"<width>x<height>@<rate>\", found \"{}\"", value);
+
+        if value.eq_ignore_ascii_case("desktop") {
+            return Ok(FullscreenResolution::Desktop);
+        }
+
+        let (size, refresh_rate) = match value.split_once('@') {
+            Some((size, rate)) => (size, Some(rate.trim().parse::<u32>().map_err(|_| expected())?)),
+            None => (value, None),
+        };
+        let (width, height) = size.split_once(['x', 'X']).ok_or_else(expected)?;
+        let width = width.trim().parse::<u32>().map_err(|_| expected())?;
+        let height = height.trim().parse::<u32>().map_err(|_| expected())?;
+        if width == 0 || height == 0 || refresh_rate == Some(0) {
+            return Err(expected());
+        }
+
+        return Ok(FullscreenResolution::Exact {
+            width,
+            height,
+            refresh_rate,
+        });
+    }
+
+}
+
+impl TryFrom<String> for FullscreenResolution {
+
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        return value.parse();
+    }
+
+}
+
+impl Display for FullscreenResolution {
+
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        return match self {
+            FullscreenResolution::Desktop => write!(f, "desktop"),
+            FullscreenResolution::Exact { width, height, refresh_rate: None } => write!(f, "{}x{}", width, height),
+            FullscreenResolution::Exact { width, height, refresh_rate: Some(rate) } => write!(f, "{}x{}@{}", width, height, rate),
+        }
+    }
+
+}
+
+impl From<FullscreenResolution> for String {
+
+    fn from(resolution: FullscreenResolution) -> Self {
+        return resolution.to_string();
+    }
+
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+//                                          Monitor Info                                          //
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct MonitorInfo {
+    // Position in the list of connected monitors, the primary monitor is always 0
+    pub index: usize,
+    pub name: String,
+    pub x: i32,
+    pub y: i32,
+    // Position and size of the area not covered by task bars and docks
+    pub work_area: (i32, i32, i32, i32),
+    // Size in millimetres
+    pub physical_size: (i32, i32),
+    pub content_scale: (f32, f32),
+    pub video_mode: Option<VideoMode>,
+    pub video_modes: Vec<VideoMode>,
+}
+
+impl MonitorInfo {
+
+    pub(crate) fn new(index: usize, monitor: &glfw::Monitor) -> MonitorInfo {
+        return MonitorInfo {
+            index,
+            name: monitor.get_name().unwrap_or_default(),
+            x: monitor.get_pos().0,
+            y: monitor.get_pos().1,
+            work_area: monitor.get_workarea(),
+            physical_size: monitor.get_physical_size(),
+            content_scale: monitor.get_content_scale(),
+            video_mode: monitor.get_video_mode().map(VideoMode::from),
+            video_modes: monitor.get_video_modes().into_iter().map(VideoMode::from).collect(),
+        }
+    }
+
+    pub fn is_primary(&self) -> bool {
+        return self.index == 0;
+    }
+
+    // Video mode matching the requested resolution, the highest refresh rate wins when none was
+    // requested
+    pub fn find_video_mode(&self, resolution: FullscreenResolution) -> Option<VideoMode> {
+        return match resolution {
+            FullscreenResolution::Desktop => self.video_mode,
+            FullscreenResolution::Exact { width, height, refresh_rate } => {
+                self.video_modes.iter()
+                    .filter(|mode| mode.width == width && mode.height == height)
+                    .filter(|mode| refresh_rate.map(|rate| mode.refresh_rate == rate).unwrap_or(true))
+                    .max_by_key(|mode| mode.refresh_rate)
+                    .copied()
+            }
+        }
+    }
+
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+//                                            Monitors                                            //
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+pub fn get_monitors(glfw: &mut Glfw) -> Vec<MonitorInfo> {
+    return glfw.with_connected_monitors(|_, monitors| {
+        return monitors.iter()
+            .enumerate()
+            .map(|(index, monitor)| MonitorInfo::new(index, monitor))
+            .collect();
+    });
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+//                                             Tests                                              //
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mode(width: u32, height: u32, refresh_rate: u32) -> VideoMode {
+        return VideoMode { width, height, refresh_rate }
+    }
+
+    fn monitor(video_modes: Vec<VideoMode>) -> MonitorInfo {
+        return MonitorInfo {
+            index: 0,
+            name: "Monitor".to_string(),
+            x: 0,
+            y: 0,
+            work_area: (0, 0, 1920, 1040),
+            physical_size: (530, 300),
+            content_scale: (1.0, 1.0),
+            video_mode: Some(mode(1920, 1080, 60)),
+            video_modes,
+        }
+    }
+
+    fn exact(width: u32, height: u32, refresh_rate: Option<u32>) -> FullscreenResolution {
+        return FullscreenResolution::Exact { width, height, refresh_rate }
+    }
+
+    #[test]
+    fn resolutions_parse_from_the_config() {
+        assert_eq!("desktop".parse(), Ok(FullscreenResolution::Desktop));
+        assert_eq!("Desktop".parse(), Ok(FullscreenResolution::Desktop));
+        assert_eq!("1920x1080".parse(), Ok(exact(1920, 1080, None)));
+        assert_eq!("1920x1080@144".parse(), Ok(exact(1920, 1080, Some(144))));
+        assert_eq!("1280 X 720 @ 60".parse(), Ok(exact(1280, 720, Some(60))));
+
+        for resolution in [exact(1920, 1080, None), exact(2560, 1440, Some(165)), FullscreenResolution::Desktop] {
+            assert_eq!(resolution.to_string().parse(), Ok(resolution));
+        }
+    }
+
+    #[test]
+    fn invalid_resolutions_are_rejected() {
+        for value in ["0x1080", "1920x0", "1920x1080@0", "", "fullscreen", "1920", "1920x", "x1080", "1920x1080@", "1920x1080@fast", "-1920x1080", "1920x1080x60"] {
+            assert!(value.parse::<FullscreenResolution>().is_err(), "{}", value);
+        }
+        assert_eq!("big".parse::<FullscreenResolution>(), Err("expected \"desktop\", \"<width>x<height>\" or \"<width>x<height>@<rate>\", found \"big\"".to_string()));
+    }
+
+    #[test]
+    fn video_modes_prefer_the_highest_refresh_rate() {
+        let monitor = monitor(vec![mode(1920, 1080, 60), mode(1920, 1080, 144), mode(1920, 1080, 120), mode(2560, 1440, 165)]);
+
+        assert_eq!(monitor.find_video_mode(FullscreenResolution::Desktop), Some(mode(1920, 1080, 60)));
+        assert_eq!(monitor.find_video_mode(exact(1920, 1080, None)), Some(mode(1920, 1080, 144)));
+        assert_eq!(monitor.find_video_mode(exact(1920, 1080, Some(120))), Some(mode(1920, 1080, 120)));
+        assert_eq!(monitor.find_video_mode(exact(1920, 1080, Some(75))), None);
+        assert_eq!(monitor.find_video_mode(exact(1280, 720, None)), None);
+    }
+
+}
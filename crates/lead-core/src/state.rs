@@ -1,4 +1,6 @@
-use lead_mem::{ObjectState, singleton_mut, pointer::MutPointer};
+use lead_mem::{ObjectState, pointer::MutPointer, tracking};
+use lead_mem::lifecycle::ObjectId;
+use crate::engine::Context;
 use std::any::{Any, TypeId};
 use lead_logger::warn;
 
@@ -8,11 +10,11 @@ use lead_logger::warn;
 
 pub trait TState: Any {
 
-    fn init(&mut self);
-    fn open(&mut self);
-    fn update(&mut self, delta: f64);
-    fn close(&mut self);
-    fn dispose(&mut self);
+    fn init(&mut self, ctx: &mut Context);
+    fn open(&mut self, ctx: &mut Context);
+    fn update(&mut self, ctx: &mut Context, delta: f64);
+    fn close(&mut self, ctx: &mut Context);
+    fn dispose(&mut self, ctx: &mut Context);
 
 }
 
@@ -20,66 +22,90 @@ pub trait TState: Any {
 //                                         State Manager                                          //
 ////////////////////////////////////////////////////////////////////////////////////////////////////
 
-singleton_mut!(func: get_state_manager, STATE_MANAGER, StateManager, StateManager::new());
+#[cfg(feature = "globals")]
+pub fn get_state_manager() -> MutPointer<StateManager> {
+    return MutPointer::new(crate::get_engine().as_mut().state_manager_mut());
+}
 
 pub struct StateManager {
     states: Vec<Box<dyn TState>>,
+    state_ids: Vec<ObjectId>,
     current: Option<MutPointer<Box<dyn TState>>>,
     object_state: ObjectState,
+    lifecycle_id: ObjectId,
 }
 
 impl StateManager {
 
-    fn new() -> StateManager {
+    pub(crate) fn new(ctx: &mut Context) -> StateManager {
         return StateManager {
             states: Vec::new(),
+            state_ids: Vec::new(),
             current: None,
             object_state: ObjectState::Created,
+            lifecycle_id: ctx.lifecycle_mut().register("StateManager"),
         }
     }
 
-    pub fn init(&mut self) {
+    pub fn init(&mut self, ctx: &mut Context) {
+        let _scope = tracking::scope("StateManager");
+
         match self.object_state {
             ObjectState::Created => {
-                for state in self.states.iter_mut() {
-                    state.init();
+                for (state, id) in self.states.iter_mut().zip(self.state_ids.iter()) {
+                    state.init(ctx);
+                    Self::transition(ctx, *id, ObjectState::Initialized);
                 }
 
                 if let Some(current) = &self.current {
-                    current.as_mut().open();
+                    Self::open_state(ctx, current.as_mut());
                 }
 
                 self.object_state = ObjectState::Initialized;
+                Self::transition(ctx, self.lifecycle_id, ObjectState::Initialized);
             }
             ObjectState::Initialized => warn!("StateManager", "Failed to initialize StateManager, already initialized"),
             ObjectState::Disposed => warn!("StateManager", "Failed to initialize StateManager, already disposed"),
         }
     }
 
-    pub fn register<State: TState + 'static>(&mut self, mut state: State) {
-        match self.object_state {
-            ObjectState::Disposed => warn!("StateManager", "Failed to register state '{}', already disposed", std::any::type_name::<State>()),
-            ObjectState::Initialized => state.init(),
-            _ => {}
+    pub fn register<State: TState + 'static>(&mut self, ctx: &mut Context, mut state: State) {
+        let _scope = tracking::scope("StateManager");
+
+        // A state registered after dispose would never be disposed and show up as leaked
+        if self.object_state == ObjectState::Disposed {
+            warn!("StateManager", "Failed to register state '{}', already disposed", std::any::type_name::<State>());
+            return;
+        }
+
+        let id = ctx.lifecycle_mut().register(std::any::type_name::<State>());
+        if self.object_state == ObjectState::Initialized {
+            state.init(ctx);
+            Self::transition(ctx, id, ObjectState::Initialized);
         }
 
         self.states.push(Box::new(state));
+        self.state_ids.push(id);
+    }
+
+    pub fn open<State: TState + 'static>(&mut self, ctx: &mut Context) {
+        self.open_type(ctx, TypeId::of::<State>(), std::any::type_name::<State>());
     }
 
-    pub fn open<State: TState + 'static>(&mut self) {
+    pub(crate) fn open_type(&mut self, ctx: &mut Context, type_id: TypeId, name: &str) {
         if self.object_state == ObjectState::Disposed {
-           warn!("StateManager", "Failed to open state '{}', already disposed", std::any::type_name::<State>());
+           warn!("StateManager", "Failed to open state '{}', already disposed", name);
            return;
         }
 
         // Close current state
-        self.close();
+        self.close(ctx);
 
         // Find matching state
         for state in self.states.iter_mut() {
-            if (**state).type_id() == TypeId::of::<State>() {
+            if (**state).type_id() == type_id {
                 if self.object_state == ObjectState::Initialized {
-                    state.open();
+                    Self::open_state(ctx, state);
                 }
                 self.current = Some(MutPointer::new(state));
                 return;
@@ -87,41 +113,146 @@ impl StateManager {
         }
 
         // No matching state
-        warn!("StateManager", "Failed to open state '{}', not found", std::any::type_name::<State>());
+        warn!("StateManager", "Failed to open state '{}', not found", name);
     }
 
-    pub fn update(&mut self, delta: f64) {
+    pub fn update(&mut self, ctx: &mut Context, delta: f64) {
+        let _scope = tracking::scope("StateManager");
+
         if self.object_state == ObjectState::Initialized {
             if let Some(current) = &self.current {
-                current.as_mut().update(delta);
+                current.as_mut().update(ctx, delta);
             }
         }
     }
 
-    pub fn close(&mut self) {
+    pub fn close(&mut self, ctx: &mut Context) {
         if let Some(state) = self.current.take() {
             if self.object_state == ObjectState::Initialized {
-                state.as_mut().close();
+                Self::close_state(ctx, state.as_mut());
             }
         }
     }
 
-    pub fn dispose(&mut self) {
+    pub fn dispose(&mut self, ctx: &mut Context) {
+        let _scope = tracking::scope("StateManager");
+
         match self.object_state {
             ObjectState::Created => warn!("StateManager", "Failed to dispose StateManager, not initialized"),
             ObjectState::Initialized => {
                 if let Some(current) = self.current.take() {
-                    current.as_mut().close();
+                    Self::close_state(ctx, current.as_mut());
                 }
 
-                for state in self.states.iter_mut() {
-                    state.dispose();
+                for (state, id) in self.states.iter_mut().zip(self.state_ids.iter()) {
+                    state.dispose(ctx);
+                    Self::transition(ctx, *id, ObjectState::Disposed);
                 }
 
                 self.object_state = ObjectState::Disposed;
+                Self::transition(ctx, self.lifecycle_id, ObjectState::Disposed);
+            }
+            ObjectState::Disposed => {
+                warn!("StateManager", "Failed to dispose StateManager, already disposed");
+                Self::transition(ctx, self.lifecycle_id, ObjectState::Disposed);
             }
-            ObjectState::Disposed => warn!("StateManager", "Failed to dispose StateManager, already disposed"),
         }
     }
 
-}
\ No newline at end of file
+    // Input contexts bound to the state follow it
+    fn open_state(ctx: &mut Context, state: &mut Box<dyn TState>) {
+        ctx.input_mut().contexts_mut().set_state_open((**state).type_id(), true);
+        state.open(ctx);
+    }
+
+    fn close_state(ctx: &mut Context, state: &mut Box<dyn TState>) {
+        state.close(ctx);
+        ctx.input_mut().contexts_mut().set_state_open((**state).type_id(), false);
+    }
+
+    fn transition(ctx: &mut Context, id: ObjectId, state: ObjectState) {
+        if let Err(err) = ctx.lifecycle_mut().transition(id, state) {
+            warn!("Lifecycle", "{}", err);
+        }
+    }
+
+    /* ====================================== Getters ======================================= */
+
+    pub fn get_object_state(&self) -> ObjectState {
+        return self.object_state;
+    }
+
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+//                                             Tests                                              //
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::Engine;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    type Calls = Rc<RefCell<Vec<String>>>;
+
+    struct Menu(Calls);
+    struct Game(Calls);
+
+    macro_rules! recording_state {
+        ($state: ident) => {
+            impl TState for $state {
+                fn init(&mut self, _: &mut Context) { self.0.borrow_mut().push(format!("{} init", stringify!($state))); }
+                fn open(&mut self, _: &mut Context) { self.0.borrow_mut().push(format!("{} open", stringify!($state))); }
+                fn update(&mut self, _: &mut Context, _: f64) { self.0.borrow_mut().push(format!("{} update", stringify!($state))); }
+                fn close(&mut self, _: &mut Context) { self.0.borrow_mut().push(format!("{} close", stringify!($state))); }
+                fn dispose(&mut self, _: &mut Context) { self.0.borrow_mut().push(format!("{} dispose", stringify!($state))); }
+            }
+        };
+    }
+
+    recording_state!(Menu);
+    recording_state!(Game);
+
+    fn take(calls: &Calls) -> Vec<String> {
+        return calls.borrow_mut().drain(..).collect();
+    }
+
+    #[test]
+    fn states_follow_the_engine_lifecycle() {
+        let calls = Calls::default();
+        let mut engine = Engine::headless();
+        engine.register(Menu(calls.clone()));
+        engine.register(Game(calls.clone()));
+        engine.open::<Menu>();
+        assert!(take(&calls).is_empty());
+
+        engine.init();
+        assert_eq!(take(&calls), ["Menu init", "Game init", "Menu open"]);
+
+        engine.step(0.1);
+        engine.open::<Game>();
+        engine.step(0.1);
+        assert_eq!(take(&calls), ["Menu update", "Menu close", "Game open", "Game update"]);
+
+        engine.dispose();
+        assert_eq!(take(&calls), ["Game close", "Menu dispose", "Game dispose"]);
+        assert!(engine.context().lifecycle().report().is_clean());
+    }
+
+    #[test]
+    fn registering_after_dispose_is_rejected() {
+        let calls = Calls::default();
+        let mut engine = Engine::headless();
+        engine.register(Menu(calls.clone()));
+        engine.init();
+        engine.dispose();
+
+        engine.register(Game(calls.clone()));
+        engine.open::<Game>();
+        assert_eq!(take(&calls), ["Menu init", "Menu dispose"]);
+        assert!(engine.context().lifecycle().report().is_clean());
+    }
+
+}
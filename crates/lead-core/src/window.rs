@@ -1,30 +1,372 @@
-use glfw::{Context, SwapInterval, WindowEvent, WindowMode};
-use crate::{get_glfw, input::{Action, get_input}};
-use lead_mem::singleton_mut;
-use lead_logger::{warn, critical};
+use glfw::{Context, Glfw, SwapInterval, WindowMode};
+use crate::config::{deserialize_limit, serialize_limit, ConfigSection, ValidationError};
+use crate::clipboard::{Clipboard, MemoryClipboard};
+use crate::event::EventBus;
+use crate::image::{Image, ImageError};
+use crate::input::{Action, Input, InputEvent};
+use crate::monitor::{get_monitors, FullscreenResolution, MonitorInfo};
+use lead_logger::{critical, info, warn};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt::Formatter;
+use std::path::{Path, PathBuf};
 use std::sync::mpsc::Receiver;
-use toml::Value;
 
 ////////////////////////////////////////////////////////////////////////////////////////////////////
 //                                           Update Cap                                           //
 ////////////////////////////////////////////////////////////////////////////////////////////////////
 
-#[derive(Copy, Clone, Eq, PartialEq)]
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
 pub enum UpdateCap {
     Vsync,
     Unlimited,
     Cap(u32),
 }
 
+impl<'de> Deserialize<'de> for UpdateCap {
+
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct UpdateCapVisitor;
+
+        impl<'de> serde::de::Visitor<'de> for UpdateCapVisitor {
+
+            type Value = UpdateCap;
+
+            fn expecting(&self, f: &mut Formatter) -> std::fmt::Result {
+                return write!(f, "\"vsync\", \"unlimited\" or a positive integer");
+            }
+
+            fn visit_i64<E: serde::de::Error>(self, value: i64) -> Result<Self::Value, E> {
+                return match u32::try_from(value) {
+                    Ok(value) if value > 0 => Ok(UpdateCap::Cap(value)),
+                    _ => Err(E::invalid_value(serde::de::Unexpected::Signed(value), &self)),
+                }
+            }
+
+            fn visit_str<E: serde::de::Error>(self, value: &str) -> Result<Self::Value, E> {
+                if value.eq_ignore_ascii_case("vsync") {
+                    return Ok(UpdateCap::Vsync);
+                } else if value.eq_ignore_ascii_case("unlimited") {
+                    return Ok(UpdateCap::Unlimited);
+                }
+                return Err(E::invalid_value(serde::de::Unexpected::Str(value), &self));
+            }
+
+        }
+
+        return deserializer.deserialize_any(UpdateCapVisitor);
+    }
+
+}
+
+impl Serialize for UpdateCap {
+
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        return match self {
+            UpdateCap::Vsync => serializer.serialize_str("vsync"),
+            UpdateCap::Unlimited => serializer.serialize_str("unlimited"),
+            UpdateCap::Cap(cap) => serializer.serialize_u32(*cap),
+        }
+    }
+
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+//                                          Display Mode                                          //
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum DisplayMode {
+    #[default]
+    Windowed,
+    // Exclusive fullscreen, the monitor switches to the requested video mode
+    Fullscreen,
+    // Undecorated window covering the whole monitor, keeping the desktop video mode
+    Borderless,
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+//                                             Cursor                                             //
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum CursorShape {
+    #[default]
+    Arrow,
+    IBeam,
+    Crosshair,
+    Hand,
+    HResize,
+    VResize,
+}
+
+impl CursorShape {
+
+    fn to_glfw(self) -> glfw::StandardCursor {
+        return match self {
+            CursorShape::Arrow => glfw::StandardCursor::Arrow,
+            CursorShape::IBeam => glfw::StandardCursor::IBeam,
+            CursorShape::Crosshair => glfw::StandardCursor::Crosshair,
+            CursorShape::Hand => glfw::StandardCursor::Hand,
+            CursorShape::HResize => glfw::StandardCursor::HResize,
+            CursorShape::VResize => glfw::StandardCursor::VResize,
+        }
+    }
+
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum CursorMode {
+    #[default]
+    Normal,
+    // Invisible while over the window
+    Hidden,
+    // Hidden and locked to the window, the reported position is unbounded (mouse look)
+    Disabled,
+}
+
+impl CursorMode {
+
+    fn to_glfw(self) -> glfw::CursorMode {
+        return match self {
+            CursorMode::Normal => glfw::CursorMode::Normal,
+            CursorMode::Hidden => glfw::CursorMode::Hidden,
+            CursorMode::Disabled => glfw::CursorMode::Disabled,
+        }
+    }
+
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+//                                         Window Config                                          //
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct WindowConfig {
+    pub title: String,
+    pub width: u32,
+    pub height: u32,
+    #[serde(rename = "cap")]
+    pub update_cap: UpdateCap,
+    #[serde(deserialize_with = "deserialize_limit", serialize_with = "serialize_limit")]
+    pub min_width: Option<u32>,
+    #[serde(deserialize_with = "deserialize_limit", serialize_with = "serialize_limit")]
+    pub min_height: Option<u32>,
+    #[serde(deserialize_with = "deserialize_limit", serialize_with = "serialize_limit")]
+    pub max_width: Option<u32>,
+    #[serde(deserialize_with = "deserialize_limit", serialize_with = "serialize_limit")]
+    pub max_height: Option<u32>,
+    pub remember_geometry: bool,
+    pub mode: DisplayMode,
+    pub monitor: usize,
+    pub resolution: FullscreenResolution,
+    // Images of different sizes, the closest one to what the system needs is picked
+    pub icon: Vec<PathBuf>,
+    pub cursor: CursorShape,
+    // Replaces the cursor shape when set
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cursor_image: Option<PathBuf>,
+    pub cursor_hotspot: (u32, u32),
+    pub cursor_mode: CursorMode,
+    // Unaccelerated motion while the cursor is disabled, when the system supports it
+    pub raw_mouse_motion: bool,
+}
+
+impl Default for WindowConfig {
+
+    fn default() -> Self {
+        return WindowConfig {
+            title: "Title".to_string(),
+            width: 800,
+            height: 600,
+            update_cap: UpdateCap::Vsync,
+            min_width: None,
+            min_height: None,
+            max_width: None,
+            max_height: None,
+            remember_geometry: false,
+            mode: DisplayMode::Windowed,
+            monitor: 0,
+            resolution: FullscreenResolution::Desktop,
+            icon: Vec::new(),
+            cursor: CursorShape::Arrow,
+            cursor_image: None,
+            cursor_hotspot: (0, 0),
+            cursor_mode: CursorMode::Normal,
+            raw_mouse_motion: true,
+        }
+    }
+
+}
+
+impl ConfigSection for WindowConfig {
+
+    const NAME: &'static str = "window";
+
+    fn validate(&self) -> Result<(), Vec<ValidationError>> {
+        let mut errors = Vec::new();
+
+        if self.width == 0 {
+            errors.push(ValidationError::new("width", "must be greater than 0"));
+        }
+        if self.height == 0 {
+            errors.push(ValidationError::new("height", "must be greater than 0"));
+        }
+        if self.max_width == Some(0) {
+            errors.push(ValidationError::new("max-width", "must be greater than 0"));
+        }
+        if self.max_height == Some(0) {
+            errors.push(ValidationError::new("max-height", "must be greater than 0"));
+        }
+        if let (Some(min), Some(max)) = (self.min_width, self.max_width) {
+            if min > max {
+                errors.push(ValidationError::new("min-width", format!("{} is greater than max-width {}", min, max)));
+            }
+        }
+        if let (Some(min), Some(max)) = (self.min_height, self.max_height) {
+            if min > max {
+                errors.push(ValidationError::new("min-height", format!("{} is greater than max-height {}", min, max)));
+            }
+        }
+
+        if errors.is_empty() {
+            return Ok(());
+        }
+        return Err(errors);
+    }
+
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+//                                        Window Geometry                                         //
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+pub const GEOMETRY_FILE: &str = "window-state.toml";
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct WindowGeometry {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+    pub maximized: bool,
+    pub fullscreen: bool,
+    // Missing from files saved by earlier versions
+    #[serde(default)]
+    pub borderless: bool,
+}
+
+impl WindowGeometry {
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Option<WindowGeometry> {
+        let source = std::fs::read_to_string(path.as_ref()).ok()?;
+        return match toml::from_str(&source) {
+            Ok(geometry) => Some(geometry),
+            Err(err) => {
+                warn!("Window", "Failed to parse window geometry '{}': {}", path.as_ref().display(), err);
+                None
+            }
+        }
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) {
+        let path = path.as_ref();
+        if let Some(parent) = path.parent() {
+            if let Err(err) = std::fs::create_dir_all(parent) {
+                warn!("Window", "Failed to create directory '{}': {}", parent.display(), err);
+                return;
+            }
+        }
+
+        let result = toml::to_string(self)
+            .map_err(|err| err.to_string())
+            .and_then(|source| std::fs::write(path, source).map_err(|err| err.to_string()));
+        if let Err(err) = result {
+            warn!("Window", "Failed to save window geometry '{}': {}", path.display(), err);
+        }
+    }
+
+    // Keeps the window inside the work area of the monitor it overlaps the most, or of the first
+    // monitor if it is not visible on any of them anymore. Empty work areas are ignored
+    pub fn clamp_to(&mut self, work_areas: &[(i32, i32, i32, i32)]) {
+        let overlap = |&(x, y, width, height): &(i32, i32, i32, i32)| -> i64 {
+            let w = (self.x as i64 + self.width as i64).min(x as i64 + width as i64) - (self.x as i64).max(x as i64);
+            let h = (self.y as i64 + self.height as i64).min(y as i64 + height as i64) - (self.y as i64).max(y as i64);
+            return w.max(0) * h.max(0);
+        };
+
+        let mut areas = work_areas.iter().filter(|(_, _, width, height)| *width > 0 && *height > 0);
+        let area = areas.clone()
+            .filter(|area| overlap(area) > 0)
+            .max_by_key(|area| overlap(area))
+            .or(areas.next());
+
+        if let Some(&(x, y, width, height)) = area {
+            self.width = self.width.min(width as u32);
+            self.height = self.height.min(height as u32);
+            self.x = self.x.clamp(x, x.saturating_add(width - self.width as i32).max(x));
+            self.y = self.y.clamp(y, y.saturating_add(height - self.height as i32).max(y));
+        }
+    }
+
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+//                                          Window Event                                          //
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum WindowEvent {
+    // The user asked to close the window, it closes at the end of the frame unless vetoed
+    CloseRequested,
+    Moved(i32, i32),
+    Resized(u32, u32),
+    Focused(bool),
+    Iconified(bool),
+    Maximized(bool),
+    ContentScale(f32, f32),
+    // The content was damaged and has to be redrawn, e.g. while the window is being resized
+    Refresh,
+}
+
+// Window and input events of an additional window, as published on the event bus. Events of the
+// primary window are published as they are
+#[derive(Clone, PartialEq, Debug)]
+pub struct WindowTagged<E>(pub WindowId, pub E);
+
 ////////////////////////////////////////////////////////////////////////////////////////////////////
 //                                             Window                                             //
 ////////////////////////////////////////////////////////////////////////////////////////////////////
 
-singleton_mut!(func: get_window, WINDOW, Window, Window::new());
+#[cfg(feature = "globals")]
+pub fn get_window() -> lead_mem::pointer::MutPointer<Window> {
+    return lead_mem::pointer::MutPointer::new(crate::get_engine().as_mut().context_mut().window_mut());
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub struct WindowId(pub(crate) usize);
+
+impl WindowId {
+
+    // The window created from the engine config, the app closes along with it
+    pub const PRIMARY: WindowId = WindowId(0);
+
+    pub fn is_primary(&self) -> bool {
+        return *self == WindowId::PRIMARY;
+    }
+
+}
 
 pub struct Window {
-    ptr: glfw::Window,
-    events: Receiver<(f64, glfw::WindowEvent)>,
+    id: WindowId,
+    ptr: Option<glfw::Window>,
+    receiver: Option<Receiver<(f64, glfw::WindowEvent)>>,
+    should_close: bool,
+    title: String,
     width: u32,
     height: u32,
     x: i32,
@@ -35,218 +377,692 @@ pub struct Window {
     max_width: Option<u32>,
     max_height: Option<u32>,
     focused: bool,
+    iconified: bool,
+    maximized: bool,
+    content_scale: (f32, f32),
+    display_mode: DisplayMode,
+    monitor: usize,
+    resolution: FullscreenResolution,
+    // None while a custom cursor image is used
+    cursor_shape: Option<CursorShape>,
+    cursor_mode: CursorMode,
+    raw_mouse_motion: bool,
+    // The cursor jumps when it is captured or released, the next move only sets the motion origin
+    reset_motion: bool,
+    // Used instead of the system clipboard when set
+    clipboard: Option<Box<dyn Clipboard>>,
+    // Last position and size while neither maximized, iconified nor fullscreen
+    windowed: (i32, i32, u32, u32),
+    // Events of the current frame
+    events: Vec<WindowEvent>,
+    close_pending: bool,
+    close_vetoed: bool,
     // Event Flags
     resized: bool,
     moved: bool,
+    close_requested: bool,
+    focus_changed: bool,
+    iconify_changed: bool,
+    maximize_changed: bool,
+    content_scale_changed: bool,
+    refresh_requested: bool,
 }
 
 impl Window {
 
-    fn new() -> Window {
-        let glfw = get_glfw();
-
-        // Defaults Values
-        let mut width = 800;
-        let mut height = 600;
-        let mut title = "Title".to_string();
-        let mut update_cap = UpdateCap::Vsync;
-        let mut min_width = None;
-        let mut min_height = None;
-        let mut max_width = None;
-        let mut max_height = None;
-
-        // Read file
-        match std::fs::read_to_string("res/engine/window.toml") {
-            Ok(str) => {
-                match str.parse::<toml::Value>() {
-                    Ok(parsed) => {
-                        if let Some(window) = parsed.get("window") {
-                            // Width
-                            if let Some(Value::Integer(value)) = window.get("width") {
-                                width = *value as u32;
-                            } else {
-                                warn!("Window", "Failed to read property 'width' in 'window' from config file");
-                            }
-                            // Height
-                            if let Some(Value::Integer(value)) = window.get("height") {
-                                height = *value as u32;
-                            } else {
-                                warn!("Window", "Failed to read property 'height' in 'window' from config file");
-                            }
-                            // Title
-                            if let Some(Value::String(value)) = window.get("title") {
-                                title = value.clone();
-                            } else {
-                                warn!("Window", "Failed to read property 'title' in 'window' from config file");
-                            }
-                            // Update cap
-                            if let Some(value) = window.get("cap") {
-                                match value {
-                                    Value::String(str) if str.eq_ignore_ascii_case("vsync") => update_cap = UpdateCap::Vsync,
-                                    Value::String(str) if str.eq_ignore_ascii_case("unlimited") => update_cap = UpdateCap::Unlimited,
-                                    Value::Integer(value) if *value > 0 => update_cap = UpdateCap::Cap(*value as u32),
-                                    _ => warn!("Window", "Failed to read property 'cap' in 'window' from config file"),
-                                }
-                            } else {
-                                warn!("Window", "Failed to read property 'cap' in 'window' from config file");
-                            }
-                            // min-width
-                            if let Some(value) = window.get("min-width") {
-                                match value {
-                                    Value::String(str) if str.eq_ignore_ascii_case("unset") => min_width = None,
-                                    Value::Integer(value) if *value >= 0 => min_width = Some(*value as u32),
-                                    _ => warn!("Window", "Failed to read property 'min-width' in 'window' from config file"),
-                                }
-                            } else {
-                                warn!("Window", "Failed to read property 'min-width' in 'window' from config file");
-                            }
-                            // min-height
-                            if let Some(value) = window.get("min-height") {
-                                match value {
-                                    Value::String(str) if str.eq_ignore_ascii_case("unset") => min_height = None,
-                                    Value::Integer(value) if *value >= 0 => min_height = Some(*value as u32),
-                                    _ => warn!("Window", "Failed to read property 'min-height' in 'window' from config file"),
-                                }
-                            } else {
-                                warn!("Window", "Failed to read property 'min-height' in 'window' from config file");
-                            }
-                            // max-width
-                            if let Some(value) = window.get("max-width") {
-                                match value {
-                                    Value::String(str) if str.eq_ignore_ascii_case("unset") => max_width = None,
-                                    Value::Integer(value) if *value > 0 => max_width = Some(*value as u32),
-                                    _ => warn!("Window", "Failed to read property 'max-width' in 'window' from config file"),
-                                }
-                            } else {
-                                warn!("Window", "Failed to read property 'max-width' in 'window' from config file");
-                            }
-                            // max-height
-                            if let Some(value) = window.get("max-height") {
-                                match value {
-                                    Value::String(str) if str.eq_ignore_ascii_case("unset") => max_height = None,
-                                    Value::Integer(value) if *value > 0 => max_height = Some(*value as u32),
-                                    _ => warn!("Window", "Failed to read property 'max-height' in 'window' from config file"),
-                                }
-                            } else {
-                                warn!("Window", "Failed to read property 'max-height' in 'window' from config file");
-                            }
-                        } else {
-                            warn!("Window", "Failed to read structure 'window' from config file");
-                        }
-                    }
-                    Err(err) => warn!("Window", "Failed to parse config file: {}", err),
-                }
+    // Without a glfw instance the window is headless: no native window is created and only the
+    // tracked properties are updated
+    pub(crate) fn new(id: WindowId, glfw: Option<Glfw>, config: &WindowConfig) -> Window {
+        return Window::create(id, glfw, None, config);
+    }
+
+    // Additional window sharing the OpenGL objects of this one, headless if this one is
+    pub(crate) fn new_shared(&self, id: WindowId, config: &WindowConfig) -> Window {
+        let glfw = self.ptr.as_ref().map(|ptr| ptr.glfw.clone());
+        return Window::create(id, glfw, self.ptr.as_ref(), config);
+    }
+
+    fn create(id: WindowId, glfw: Option<Glfw>, share: Option<&glfw::Window>, config: &WindowConfig) -> Window {
+        let WindowConfig { title, width, height, update_cap, min_width, min_height, max_width, max_height, mode, monitor, resolution, .. } = config.clone();
+
+        let mut window = Window {
+            id,
+            ptr: None,
+            receiver: None,
+            should_close: false,
+            title: title.clone(),
+            width,
+            height,
+            x: 0,
+            y: 0,
+            min_width,
+            min_height,
+            max_width,
+            max_height,
+            update_cap,
+            focused: false,
+            iconified: false,
+            maximized: false,
+            content_scale: (1.0, 1.0),
+            display_mode: mode,
+            monitor,
+            resolution,
+            cursor_shape: if config.cursor_image.is_none() { Some(config.cursor) } else { None },
+            cursor_mode: config.cursor_mode,
+            raw_mouse_motion: config.raw_mouse_motion,
+            reset_motion: false,
+            clipboard: None,
+            windowed: (0, 0, width, height),
+            events: Vec::new(),
+            close_pending: false,
+            close_vetoed: false,
+            resized: false,
+            moved: false,
+            close_requested: false,
+            focus_changed: false,
+            iconify_changed: false,
+            maximize_changed: false,
+            content_scale_changed: false,
+            refresh_requested: false,
+        };
+
+        let mut glfw = match glfw {
+            Some(glfw) => glfw,
+            None => {
+                window.clipboard = Some(Box::new(MemoryClipboard::new()));
+                return window;
             }
-            Err(err) => warn!("Window", "Failed to read config file: {}", err),
-        }
+        };
 
-        if let Some((mut ptr, events)) = glfw.as_mut().create_window(width, height, title.as_str(), WindowMode::Windowed) {
+        let created = match share {
+            Some(share) => share.create_shared(width, height, title.as_str(), WindowMode::Windowed),
+            None => glfw.create_window(width, height, title.as_str(), WindowMode::Windowed),
+        };
+
+        if let Some((mut ptr, events)) = created {
             ptr.set_all_polling(true);
+            // Key events report the state of Caps Lock and Num Lock
+            ptr.set_store_lock_key_mods(true);
             ptr.make_current();
 
             ptr.set_size_limits(min_width, min_height, max_width, max_height);
 
-            if update_cap == UpdateCap::Vsync {
-                glfw.as_mut().set_swap_interval(SwapInterval::Sync(1));
+            // Only the primary window waits for vsync, every vsynced window would divide the frame
+            // rate once more
+            if id.is_primary() && update_cap == UpdateCap::Vsync {
+                glfw.set_swap_interval(SwapInterval::Sync(1));
             } else {
-                glfw.as_mut().set_swap_interval(SwapInterval::None);
+                glfw.set_swap_interval(SwapInterval::None);
             }
 
             gl::load_with(|symbol| ptr.get_proc_address(symbol) as *const _);
 
-            return Window {
-                ptr,
-                events,
-                width,
-                height,
-                x: 0,
-                y: 0,
-                min_width,
-                min_height,
-                max_width,
-                max_height,
-                update_cap,
-                focused: false,
-                resized: false,
-                moved: false,
+            let (x, y) = ptr.get_pos();
+            window.x = x;
+            window.y = y;
+            window.windowed = (x, y, width, height);
+            window.focused = ptr.is_focused();
+            window.iconified = ptr.is_iconified();
+            window.maximized = ptr.is_maximized();
+            window.content_scale = ptr.get_content_scale();
+            window.ptr = Some(ptr);
+            window.receiver = Some(events);
+
+            if window.display_mode != DisplayMode::Windowed {
+                window.apply_display_mode();
             }
+            window.apply_cursor_config(config);
+            if !config.icon.is_empty() {
+                if let Err(err) = window.load_icon(&config.icon) {
+                    warn!("Window", "Failed to set the window icon: {}", err);
+                }
+            }
+            return window;
         } else {
             critical!("GLFW",  "Failed to create Window!");
         }
     }
 
+    pub(crate) fn get_glfw(&self) -> Option<Glfw> {
+        return self.ptr.as_ref().map(|ptr| ptr.glfw.clone());
+    }
+
+    pub fn is_headless(&self) -> bool {
+        return self.ptr.is_none();
+    }
+
     pub fn should_close(&self) -> bool {
-        return self.ptr.should_close();
+        return match &self.ptr {
+            Some(ptr) => ptr.should_close(),
+            None => self.should_close,
+        }
+    }
+
+    pub fn set_should_close(&mut self, should_close: bool) {
+        self.should_close = should_close;
+        if let Some(ptr) = &mut self.ptr {
+            ptr.set_should_close(should_close);
+        }
+    }
+
+    // Makes the OpenGL context of this window current, required before rendering into it
+    pub fn make_current(&mut self) {
+        if let Some(ptr) = &mut self.ptr {
+            ptr.make_current();
+        }
     }
 
     pub fn swap_buffers(&mut self) {
-        self.ptr.swap_buffers();
+        if let Some(ptr) = &mut self.ptr {
+            ptr.swap_buffers();
+        }
+    }
+
+    // Asks for the window to close as if the user clicked its close button
+    pub fn request_close(&mut self) {
+        self.close_pending = true;
+    }
+
+    // Called by a state while handling a close request to keep the window open
+    pub fn veto_close(&mut self) {
+        if self.close_requested {
+            self.close_vetoed = true;
+        }
     }
 
-    pub fn process_events(&mut self) {
+    // Closes the window once the states had a chance to veto the close request of this frame
+    pub(crate) fn resolve_close_request(&mut self) {
+        if self.close_requested && !self.close_vetoed {
+            self.set_should_close(true);
+        }
+    }
+
+    fn push_event(&mut self, event: WindowEvent) {
+        match event {
+            WindowEvent::CloseRequested => {
+                self.close_requested = true;
+                self.close_vetoed = false;
+            }
+            WindowEvent::Moved(x, y) => {
+                self.x = x;
+                self.y = y;
+                self.moved = true;
+            }
+            WindowEvent::Resized(width, height) => {
+                self.width = width;
+                self.height = height;
+                self.resized = true;
+            }
+            WindowEvent::Focused(focused) => {
+                self.focused = focused;
+                self.focus_changed = true;
+            }
+            WindowEvent::Iconified(iconified) => {
+                self.iconified = iconified;
+                self.iconify_changed = true;
+            }
+            WindowEvent::Maximized(maximized) => {
+                self.maximized = maximized;
+                self.maximize_changed = true;
+            }
+            WindowEvent::ContentScale(x, y) => {
+                self.content_scale = (x, y);
+                self.content_scale_changed = true;
+            }
+            WindowEvent::Refresh => self.refresh_requested = true,
+        }
+        self.events.push(event);
+    }
+
+    // Receives the events of every window, called once per frame before processing them
+    pub fn poll_events(&mut self) {
+        if let Some(ptr) = &mut self.ptr {
+            ptr.glfw.poll_events();
+        }
+    }
+
+    // Handles the events received by the last poll. Window and input events are also published on
+    // the event bus when one is given, in the order they were received. Additional windows publish
+    // them as WindowTagged
+    pub fn process_events(&mut self, input: &mut Input, bus: Option<&mut EventBus>) {
         // Reset flags
+        self.events.clear();
         self.resized = false;
         self.moved = false;
+        self.close_requested = false;
+        self.close_vetoed = false;
+        self.focus_changed = false;
+        self.iconify_changed = false;
+        self.maximize_changed = false;
+        self.content_scale_changed = false;
+        self.refresh_requested = false;
+
+        input.update();
+        if self.reset_motion {
+            self.reset_motion = false;
+            input.mouse.reset_motion();
+        }
+
+        if self.close_pending {
+            self.close_pending = false;
+            self.push_event(WindowEvent::CloseRequested);
+        }
 
-        let glfw = get_glfw();
-        glfw.as_mut().poll_events();
+        let mut input_events = Vec::new();
+        if let (Some(mut ptr), Some(receiver)) = (self.ptr.take(), self.receiver.take()) {
+            self.handle_events(&mut ptr, &receiver, input, &mut input_events);
+            self.ptr = Some(ptr);
+            self.receiver = Some(receiver);
+        }
+        for event in input.take_injected() {
+            input_events.extend(input.handle_event(input.get_time(), event));
+        }
 
-        let input = get_input();
-        input.as_mut().update();
-        let mouse = &mut input.as_mut().mouse;
-        let keyboard = &mut input.as_mut().keyboard;
+        if let Some(bus) = bus {
+            for event in self.events.iter() {
+                self.publish(bus, *event);
+            }
+            for event in input_events {
+                self.publish(bus, event);
+            }
+        }
+    }
 
-        for (_, event) in glfw::flush_messages(&self.events) {
+    pub(crate) fn publish<E: 'static>(&self, bus: &mut EventBus, event: E) {
+        if self.id.is_primary() {
+            bus.publish(event);
+        } else {
+            bus.publish(WindowTagged(self.id, event));
+        }
+    }
+
+    fn handle_events(&mut self, ptr: &mut glfw::Window, receiver: &Receiver<(f64, glfw::WindowEvent)>, input: &mut Input, input_events: &mut Vec<InputEvent>) {
+        for (time, event) in glfw::flush_messages(receiver) {
             match event {
-                WindowEvent::Pos(x, y) => {
-                    self.x = x;
-                    self.y = y;
-                    self.moved = true;
-                }
-                WindowEvent::Focus(focused) => self.focused = focused,
-                WindowEvent::FramebufferSize(width, height) => {
-                    self.width = width as u32;
-                    self.height = height as u32;
-                    self.resized = true;
+                glfw::WindowEvent::Close => {
+                    // The window only closes once the request was not vetoed
+                    ptr.set_should_close(false);
+                    self.push_event(WindowEvent::CloseRequested);
                 }
-                WindowEvent::MouseButton(button, action, _) => {
-                    match action {
-                        glfw::Action::Press => mouse.buttons[button as usize] = Action::Pressed,
-                        glfw::Action::Release => mouse.buttons[button as usize] = Action::Released,
-                        _ => {}
+                glfw::WindowEvent::Pos(x, y) => self.push_event(WindowEvent::Moved(x, y)),
+                glfw::WindowEvent::FramebufferSize(width, height) => self.push_event(WindowEvent::Resized(width as u32, height as u32)),
+                glfw::WindowEvent::Focus(focused) => self.push_event(WindowEvent::Focused(focused)),
+                glfw::WindowEvent::Iconify(iconified) => self.push_event(WindowEvent::Iconified(iconified)),
+                glfw::WindowEvent::Maximize(maximized) => self.push_event(WindowEvent::Maximized(maximized)),
+                glfw::WindowEvent::ContentScale(x, y) => self.push_event(WindowEvent::ContentScale(x, y)),
+                glfw::WindowEvent::Refresh => self.push_event(WindowEvent::Refresh),
+                glfw::WindowEvent::MouseButton(button, action, _) => {
+                    if let Some(action) = Action::from_glfw(action) {
+                        input_events.extend(input.handle_event(time, InputEvent::MouseButton(button, action)));
                     }
                 }
-                WindowEvent::CursorPos(x, y) => {
-                    mouse.x = x;
-                    mouse.y = y;
+                glfw::WindowEvent::CursorPos(x, y) => input_events.extend(input.handle_event(time, InputEvent::CursorMoved(x, y))),
+                glfw::WindowEvent::CursorEnter(cursor_in) => input_events.extend(input.handle_event(time, InputEvent::CursorEntered(cursor_in))),
+                glfw::WindowEvent::Scroll(x, y) => input_events.extend(input.handle_event(time, InputEvent::Scroll(x, y))),
+                glfw::WindowEvent::Key(key, scancode, action, modifiers) => {
+                    let event = match Action::from_glfw(action) {
+                        Some(action) => InputEvent::Key(key, scancode, action, modifiers),
+                        None => InputEvent::KeyRepeat(key, scancode, modifiers),
+                    };
+                    input_events.extend(input.handle_event(time, event));
                 }
-                WindowEvent::CursorEnter(cursor_in) => {
-                    mouse.cursor_in = cursor_in;
+                glfw::WindowEvent::Char(chr) => input_events.extend(input.handle_event(time, InputEvent::Char(chr))),
+                glfw::WindowEvent::FileDrop(files) => {
+                    for file in files {
+                        input_events.extend(input.handle_event(time, InputEvent::FileDropped(file)));
+                    }
                 }
-                WindowEvent::Scroll(x, y) => {
-                    mouse.scroll_x = x;
-                    mouse.scroll_y = y;
+                _ => {}
+            }
+        }
+
+        if (self.moved || self.resized) && self.display_mode == DisplayMode::Windowed && !self.maximized && !self.iconified {
+            let (x, y) = ptr.get_pos();
+            let (width, height) = ptr.get_size();
+            self.windowed = (x, y, width as u32, height as u32);
+        }
+    }
+
+    pub fn get_geometry(&self) -> WindowGeometry {
+        let (x, y, width, height) = self.windowed;
+        return WindowGeometry {
+            x,
+            y,
+            width,
+            height,
+            maximized: self.ptr.as_ref().map(|ptr| ptr.is_maximized()).unwrap_or(false),
+            fullscreen: self.display_mode == DisplayMode::Fullscreen,
+            borderless: self.display_mode == DisplayMode::Borderless,
+        }
+    }
+
+    pub fn save_geometry<P: AsRef<Path>>(&self, path: P) {
+        if !self.is_headless() {
+            self.get_geometry().save(path);
+        }
+    }
+
+    pub fn restore_geometry<P: AsRef<Path>>(&mut self, path: P) {
+        let ptr = match &mut self.ptr {
+            Some(ptr) => ptr,
+            None => return,
+        };
+        let mut geometry = match WindowGeometry::load(path) {
+            Some(geometry) => geometry,
+            None => return,
+        };
+
+        let work_areas: Vec<(i32, i32, i32, i32)> = ptr.glfw.with_connected_monitors(|_, monitors| {
+            return monitors.iter().map(|monitor| monitor.get_workarea()).collect();
+        });
+        geometry.clamp_to(&work_areas);
+
+        self.windowed = (geometry.x, geometry.y, geometry.width, geometry.height);
+        if self.display_mode == DisplayMode::Windowed {
+            self.set_pos(geometry.x, geometry.y);
+            self.set_size(geometry.width, geometry.height);
+        }
+
+        if geometry.fullscreen {
+            self.set_display_mode(DisplayMode::Fullscreen);
+        } else if geometry.borderless {
+            self.set_display_mode(DisplayMode::Borderless);
+        } else if geometry.maximized && self.display_mode == DisplayMode::Windowed {
+            if let Some(ptr) = &mut self.ptr {
+                ptr.maximize();
+            }
+        }
+    }
+
+    /* ==================================== Display Mode ==================================== */
+
+    // Connected monitors, empty when headless
+    pub fn get_monitors(&self) -> Vec<MonitorInfo> {
+        return match &self.ptr {
+            Some(ptr) => get_monitors(&mut ptr.glfw.clone()),
+            None => Vec::new(),
+        }
+    }
+
+    pub fn get_current_monitor(&self) -> Option<MonitorInfo> {
+        let mut monitors = self.get_monitors();
+        if self.monitor < monitors.len() {
+            return Some(monitors.swap_remove(self.monitor));
+        }
+        return monitors.into_iter().next();
+    }
+
+    pub fn set_windowed(&mut self) {
+        self.set_display_mode(DisplayMode::Windowed);
+    }
+
+    pub fn set_fullscreen(&mut self, monitor: usize, resolution: FullscreenResolution) {
+        self.monitor = monitor;
+        self.resolution = resolution;
+        self.set_display_mode(DisplayMode::Fullscreen);
+    }
+
+    pub fn set_borderless(&mut self, monitor: usize) {
+        self.monitor = monitor;
+        self.set_display_mode(DisplayMode::Borderless);
+    }
+
+    pub fn set_display_mode(&mut self, mode: DisplayMode) {
+        self.display_mode = mode;
+        self.apply_display_mode();
+    }
+
+    pub fn set_monitor(&mut self, monitor: usize) {
+        self.monitor = monitor;
+        if self.display_mode != DisplayMode::Windowed {
+            self.apply_display_mode();
+        }
+    }
+
+    pub fn set_resolution(&mut self, resolution: FullscreenResolution) {
+        self.resolution = resolution;
+        if self.display_mode == DisplayMode::Fullscreen {
+            self.apply_display_mode();
+        }
+    }
+
+    fn apply_display_mode(&mut self) {
+        let ptr = match &mut self.ptr {
+            Some(ptr) => ptr,
+            None => return,
+        };
+
+        let (mode, index, resolution) = (self.display_mode, self.monitor, self.resolution);
+        let (x, y, width, height) = self.windowed;
+        let mut glfw = ptr.glfw.clone();
+        glfw.with_connected_monitors(|_, monitors| {
+            let monitor = match monitors.get(index) {
+                Some(monitor) => Some((index, monitor)),
+                None if mode == DisplayMode::Windowed => None,
+                None => {
+                    warn!("Window", "Monitor {} is not connected, using the primary monitor", index);
+                    monitors.first().map(|monitor| (0, monitor))
                 }
-                WindowEvent::Key(key, _, action, _) => {
-                    match action {
-                        glfw::Action::Press => keyboard.keys[key as usize] = Action::Pressed,
-                        glfw::Action::Release => keyboard.keys[key as usize] = Action::Released,
-                        _ => {}
+            };
+
+            match (mode, monitor) {
+                (DisplayMode::Fullscreen, Some((index, monitor))) => {
+                    let info = MonitorInfo::new(index, monitor);
+                    let video_mode = info.find_video_mode(resolution).or_else(|| {
+                        warn!("Window", "Monitor '{}' does not support {}, using the desktop video mode", info.name, resolution);
+                        return info.video_mode;
+                    });
+
+                    if let Some(video_mode) = video_mode {
+                        ptr.set_decorated(true);
+                        ptr.set_monitor(WindowMode::FullScreen(monitor), 0, 0, video_mode.width, video_mode.height, Some(video_mode.refresh_rate));
                     }
                 }
-                WindowEvent::Char(chr) => keyboard.chars.push_front(chr),
-                WindowEvent::FileDrop(files) => {
-                    for file in files {
-                        input.as_mut().dropped_files.push(file);
+                (DisplayMode::Borderless, Some((index, monitor))) => {
+                    let info = MonitorInfo::new(index, monitor);
+                    if let Some(video_mode) = info.video_mode {
+                        ptr.set_decorated(false);
+                        ptr.set_monitor(WindowMode::Windowed, info.x, info.y, video_mode.width, video_mode.height, None);
                     }
                 }
-                _ => {}
+                (DisplayMode::Windowed, _) => {
+                    ptr.set_decorated(true);
+                    ptr.set_monitor(WindowMode::Windowed, x, y, width, height, None);
+                }
+                (_, None) => warn!("Window", "No monitor connected, staying windowed"),
             }
+        });
+    }
+
+    /* ======================================= Cursor ======================================= */
+
+    pub fn load_icon<P: AsRef<Path>>(&mut self, paths: &[P]) -> Result<(), ImageError> {
+        let images = paths.iter().map(Image::load).collect::<Result<Vec<Image>, ImageError>>()?;
+        self.set_icon(&images);
+        return Ok(());
+    }
+
+    // An empty list restores the default icon
+    pub fn set_icon(&mut self, images: &[Image]) {
+        if let Some(ptr) = &mut self.ptr {
+            ptr.set_icon_from_pixels(images.iter().map(Image::to_pixel_image).collect());
+        }
+    }
+
+    pub fn set_cursor_shape(&mut self, shape: CursorShape) {
+        self.cursor_shape = Some(shape);
+        if let Some(ptr) = &mut self.ptr {
+            ptr.set_cursor(Some(glfw::Cursor::standard(shape.to_glfw())));
+        }
+    }
+
+    // The hotspot is the pixel of the image pointing at the cursor position
+    pub fn set_cursor_image(&mut self, image: &Image, hotspot_x: u32, hotspot_y: u32) {
+        self.cursor_shape = None;
+        if let Some(ptr) = &mut self.ptr {
+            ptr.set_cursor(Some(glfw::Cursor::create_from_pixels(image.to_pixel_image(), hotspot_x, hotspot_y)));
+        }
+    }
+
+    pub fn load_cursor_image<P: AsRef<Path>>(&mut self, path: P, hotspot_x: u32, hotspot_y: u32) -> Result<(), ImageError> {
+        let image = Image::load(path)?;
+        self.set_cursor_image(&image, hotspot_x, hotspot_y);
+        return Ok(());
+    }
+
+    pub fn reset_cursor(&mut self) {
+        self.cursor_shape = Some(CursorShape::Arrow);
+        if let Some(ptr) = &mut self.ptr {
+            ptr.set_cursor(None);
+        }
+    }
+
+    pub fn set_cursor_mode(&mut self, mode: CursorMode) {
+        self.reset_motion |= self.cursor_mode != mode;
+        self.cursor_mode = mode;
+        if let Some(ptr) = &mut self.ptr {
+            ptr.set_cursor_mode(mode.to_glfw());
+        }
+        self.apply_raw_mouse_motion();
+    }
+
+    // Hides and locks the cursor for camera controls, the mouse then reports unbounded motion
+    pub fn capture_mouse(&mut self) {
+        self.set_cursor_mode(CursorMode::Disabled);
+    }
+
+    pub fn release_mouse(&mut self) {
+        self.set_cursor_mode(CursorMode::Normal);
+    }
+
+    pub fn is_mouse_captured(&self) -> bool {
+        return self.cursor_mode == CursorMode::Disabled;
+    }
+
+    pub fn set_raw_mouse_motion(&mut self, raw_mouse_motion: bool) {
+        self.raw_mouse_motion = raw_mouse_motion;
+        self.apply_raw_mouse_motion();
+    }
+
+    fn apply_raw_mouse_motion(&mut self) {
+        let enabled = self.is_raw_mouse_motion();
+        if let Some(ptr) = &mut self.ptr {
+            if ptr.uses_raw_mouse_motion() != enabled {
+                ptr.set_raw_mouse_motion(enabled);
+                self.reset_motion = true;
+            }
+        }
+    }
+
+    pub fn supports_raw_mouse_motion(&self) -> bool {
+        return self.ptr.as_ref().map(|ptr| ptr.glfw.supports_raw_motion()).unwrap_or(false);
+    }
+
+    // Whether the mouse currently reports raw motion, only while captured
+    pub fn is_raw_mouse_motion(&self) -> bool {
+        return self.raw_mouse_motion && self.is_mouse_captured() && self.supports_raw_mouse_motion();
+    }
+
+    fn apply_cursor_config(&mut self, config: &WindowConfig) {
+        match &config.cursor_image {
+            Some(path) => {
+                let (x, y) = config.cursor_hotspot;
+                if let Err(err) = self.load_cursor_image(path, x, y) {
+                    warn!("Window", "Failed to set the cursor image: {}", err);
+                    self.set_cursor_shape(config.cursor);
+                }
+            }
+            None if config.cursor == CursorShape::Arrow => self.reset_cursor(),
+            None => self.set_cursor_shape(config.cursor),
+        }
+        self.set_cursor_mode(config.cursor_mode);
+    }
+
+    /* ===================================== Clipboard ====================================== */
+
+    pub fn get_clipboard_text(&self) -> Option<String> {
+        if let Some(clipboard) = &self.clipboard {
+            return clipboard.get_text();
+        }
+        return self.ptr.as_ref().and_then(|ptr| ptr.get_clipboard_string());
+    }
+
+    pub fn set_clipboard_text(&mut self, text: &str) {
+        if let Some(clipboard) = &mut self.clipboard {
+            clipboard.set_text(text);
+        } else if let Some(ptr) = &mut self.ptr {
+            ptr.set_clipboard_string(text);
+        }
+    }
+
+    // Replaces the system clipboard, e.g. with a MemoryClipboard in tests
+    pub fn set_clipboard(&mut self, clipboard: Box<dyn Clipboard>) {
+        self.clipboard = Some(clipboard);
+    }
+
+    // Goes back to the system clipboard, headless windows keep an in-memory one
+    pub fn reset_clipboard(&mut self) {
+        self.clipboard = match self.ptr {
+            Some(_) => None,
+            None => Some(Box::new(MemoryClipboard::new())),
+        };
+    }
+
+    // Applies the properties which differ between two versions of the config
+    pub fn apply_config_changes(&mut self, old: &WindowConfig, new: &WindowConfig) {
+        if old.title != new.title {
+            info!("Window", "Config 'title' changed from '{}' to '{}'", old.title, new.title);
+            self.set_title(&new.title);
+        }
+        if old.width != new.width || old.height != new.height {
+            info!("Window", "Config size changed from {}x{} to {}x{}", old.width, old.height, new.width, new.height);
+            self.set_size(new.width, new.height);
+        }
+        if old.update_cap != new.update_cap {
+            info!("Window", "Config 'cap' changed from {:?} to {:?}", old.update_cap, new.update_cap);
+            self.set_update_cap(new.update_cap);
+        }
+
+        let old_limits = (old.min_width, old.min_height, old.max_width, old.max_height);
+        let new_limits = (new.min_width, new.min_height, new.max_width, new.max_height);
+        if old_limits != new_limits {
+            info!("Window", "Config size limits changed from {:?} to {:?}", old_limits, new_limits);
+            self.set_size_limits(new.min_width, new.min_height, new.max_width, new.max_height);
+        }
+
+        if old.icon != new.icon {
+            info!("Window", "Config 'icon' changed from {:?} to {:?}", old.icon, new.icon);
+            if let Err(err) = self.load_icon(&new.icon) {
+                warn!("Window", "Failed to set the window icon: {}", err);
+            }
+        }
+        if (old.cursor, &old.cursor_image, old.cursor_hotspot, old.cursor_mode) != (new.cursor, &new.cursor_image, new.cursor_hotspot, new.cursor_mode) {
+            info!("Window", "Config cursor changed");
+            self.apply_cursor_config(new);
+        }
+        if old.raw_mouse_motion != new.raw_mouse_motion {
+            info!("Window", "Config 'raw-mouse-motion' changed from {} to {}", old.raw_mouse_motion, new.raw_mouse_motion);
+            self.set_raw_mouse_motion(new.raw_mouse_motion);
+        }
+
+        if (old.mode, old.monitor, old.resolution) != (new.mode, new.monitor, new.resolution) {
+            info!("Window", "Config display mode changed from {:?} on monitor {} to {:?} on monitor {}", old.mode, old.monitor, new.mode, new.monitor);
+            self.monitor = new.monitor;
+            self.resolution = new.resolution;
+            self.set_display_mode(new.mode);
         }
     }
 
     /* ====================================== Getters ======================================= */
 
+    pub fn get_id(&self) -> WindowId {
+        return self.id;
+    }
+
+    pub fn get_title(&self) -> &str {
+        return &self.title;
+    }
+
     pub fn get_width(&self) -> u32 {
         return self.width;
     }
@@ -287,12 +1103,61 @@ impl Window {
         return self.focused;
     }
 
+    pub fn is_iconified(&self) -> bool {
+        return self.iconified;
+    }
+
+    pub fn is_maximized(&self) -> bool {
+        return self.maximized;
+    }
+
+    pub fn get_content_scale(&self) -> (f32, f32) {
+        return self.content_scale;
+    }
+
+    pub fn get_cursor_shape(&self) -> Option<CursorShape> {
+        return self.cursor_shape;
+    }
+
+    pub fn get_cursor_mode(&self) -> CursorMode {
+        return self.cursor_mode;
+    }
+
+    pub fn get_display_mode(&self) -> DisplayMode {
+        return self.display_mode;
+    }
+
+    pub fn is_fullscreen(&self) -> bool {
+        return self.display_mode == DisplayMode::Fullscreen;
+    }
+
+    pub fn is_borderless(&self) -> bool {
+        return self.display_mode == DisplayMode::Borderless;
+    }
+
+    pub fn get_monitor(&self) -> usize {
+        return self.monitor;
+    }
+
+    pub fn get_resolution(&self) -> FullscreenResolution {
+        return self.resolution;
+    }
+
     /* ====================================== Setters ======================================= */
 
+    pub fn set_title(&mut self, title: &str) {
+        self.title = title.to_string();
+        if let Some(ptr) = &mut self.ptr {
+            ptr.set_title(title);
+        }
+    }
+
     pub fn set_size(&mut self, width: u32, height: u32) {
         self.width = width;
         self.height = height;
-        self.ptr.set_size(width as i32, height as i32);
+        if let Some(ptr) = &mut self.ptr {
+            ptr.set_size(width as i32, height as i32);
+        }
     }
 
     pub fn set_width(&mut self, width: u32) {
@@ -306,7 +1171,9 @@ impl Window {
     pub fn set_pos(&mut self, x: i32, y: i32) {
         self.x = x;
         self.y = y;
-        self.ptr.set_pos(x, y);
+        if let Some(ptr) = &mut self.ptr {
+            ptr.set_pos(x, y);
+        }
     }
 
     pub fn set_x(&mut self, x: i32) {
@@ -320,10 +1187,13 @@ impl Window {
     pub fn set_update_cap(&mut self, update_cap: UpdateCap) {
         self.update_cap = update_cap;
 
-        if update_cap == UpdateCap::Vsync {
-            get_glfw().as_mut().set_swap_interval(SwapInterval::Sync(1));
-        } else {
-            get_glfw().as_mut().set_swap_interval(SwapInterval::None);
+        // The swap interval applies to the current context, which is the one of the primary window
+        if let Some(ptr) = self.ptr.as_mut().filter(|_| self.id.is_primary()) {
+            if update_cap == UpdateCap::Vsync {
+                ptr.glfw.set_swap_interval(SwapInterval::Sync(1));
+            } else {
+                ptr.glfw.set_swap_interval(SwapInterval::None);
+            }
         }
     }
 
@@ -332,7 +1202,9 @@ impl Window {
         self.min_height = min_height;
         self.max_width = max_width;
         self.max_height = max_height;
-        self.ptr.set_size_limits(self.min_width, self.min_height, self.max_width, self.max_height);
+        if let Some(ptr) = &mut self.ptr {
+            ptr.set_size_limits(self.min_width, self.min_height, self.max_width, self.max_height);
+        }
     }
 
     pub fn set_min_size(&mut self, min_width: Option<u32>, min_height: Option<u32>) {
@@ -369,4 +1241,111 @@ impl Window {
         return self.moved;
     }
 
-}
\ No newline at end of file
+    pub fn close_requested(&self) -> bool {
+        return self.close_requested;
+    }
+
+    pub fn focus_changed(&self) -> bool {
+        return self.focus_changed;
+    }
+
+    pub fn iconify_changed(&self) -> bool {
+        return self.iconify_changed;
+    }
+
+    pub fn maximize_changed(&self) -> bool {
+        return self.maximize_changed;
+    }
+
+    pub fn content_scale_changed(&self) -> bool {
+        return self.content_scale_changed;
+    }
+
+    pub fn refresh_requested(&self) -> bool {
+        return self.refresh_requested;
+    }
+
+    // Every window event received this frame, in order
+    pub fn get_events(&self) -> &[WindowEvent] {
+        return &self.events;
+    }
+
+}
+
+// Lets text buffers copy and paste through the window, see TextBuffer::update
+impl Clipboard for Window {
+
+    fn get_text(&self) -> Option<String> {
+        return self.get_clipboard_text();
+    }
+
+    fn set_text(&mut self, text: &str) {
+        self.set_clipboard_text(text);
+    }
+
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+//                                             Tests                                              //
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn geometry(x: i32, y: i32, width: u32, height: u32) -> WindowGeometry {
+        return WindowGeometry { x, y, width, height, maximized: false, fullscreen: false, borderless: false }
+    }
+
+    fn clamped(mut geometry: WindowGeometry, work_areas: &[(i32, i32, i32, i32)]) -> (i32, i32, u32, u32) {
+        geometry.clamp_to(work_areas);
+        return (geometry.x, geometry.y, geometry.width, geometry.height);
+    }
+
+    #[test]
+    fn clamp_keeps_visible_windows_in_place() {
+        let areas = [(0, 0, 1920, 1040), (1920, 0, 2560, 1400)];
+        assert_eq!(clamped(geometry(100, 100, 800, 600), &areas), (100, 100, 800, 600));
+        assert_eq!(clamped(geometry(2000, 50, 800, 600), &areas), (2000, 50, 800, 600));
+    }
+
+    #[test]
+    fn clamp_moves_windows_into_the_most_overlapped_area() {
+        let areas = [(0, 0, 1920, 1040), (1920, 0, 2560, 1400)];
+        assert_eq!(clamped(geometry(1800, -50, 800, 600), &areas), (1920, 0, 800, 600));
+        assert_eq!(clamped(geometry(4300, 1300, 400, 300), &areas), (4080, 1100, 400, 300));
+    }
+
+    #[test]
+    fn clamp_moves_lost_windows_to_the_first_area() {
+        let areas = [(0, 0, 1920, 1040), (1920, 0, 2560, 1400)];
+        assert_eq!(clamped(geometry(-5000, 3000, 800, 600), &areas), (0, 440, 800, 600));
+        assert_eq!(clamped(geometry(10, 10, 3000, 2000), &areas[..1]), (0, 0, 1920, 1040));
+    }
+
+    #[test]
+    fn clamp_ignores_empty_work_areas() {
+        assert_eq!(clamped(geometry(-100, 0, 800, 600), &[(0, 0, 0, 0), (0, 0, 1920, 1040)]), (0, 0, 800, 600));
+        assert_eq!(clamped(geometry(50, 50, 800, 600), &[(0, 0, 1920, 0)]), (50, 50, 800, 600));
+        assert_eq!(clamped(geometry(50, 50, 800, 600), &[]), (50, 50, 800, 600));
+    }
+
+    #[test]
+    fn clamp_does_not_overflow() {
+        let areas = [(i32::MAX - 1000, 0, 1000, 1000)];
+        assert_eq!(clamped(geometry(i32::MAX - 10, 0, u32::MAX, 500), &areas), (i32::MAX - 1000, 0, 1000, 500));
+        assert_eq!(clamped(geometry(i32::MIN, i32::MIN, 800, 600), &[(0, 0, 1920, 1040)]), (0, 0, 800, 600));
+    }
+
+    #[test]
+    fn geometry_files_without_borderless_still_load() {
+        let geometry: WindowGeometry = toml::from_str("x = 1\ny = 2\nwidth = 3\nheight = 4\nmaximized = true\nfullscreen = false").unwrap();
+        assert!(!geometry.borderless);
+
+        let mut borderless = geometry;
+        borderless.borderless = true;
+        let source = toml::to_string(&borderless).unwrap();
+        assert_eq!(toml::from_str::<WindowGeometry>(&source).unwrap(), borderless);
+    }
+
+}
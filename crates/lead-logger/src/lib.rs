@@ -37,7 +37,7 @@ pub struct Logger {
 
 impl Logger {
 
-    fn new() -> Logger {
+    pub fn new() -> Logger {
         return Logger {
             level: LogLevel::Info
         }
@@ -59,12 +59,30 @@ impl Logger {
 
 }
 
+impl Default for Logger {
+
+    fn default() -> Self {
+        return Logger::new();
+    }
+
+}
+
 ////////////////////////////////////////////////////////////////////////////////////////////////////
 //                                             Macros                                             //
 ////////////////////////////////////////////////////////////////////////////////////////////////////
 
 #[macro_export]
 macro_rules! critical {
+    (logger: $logger: expr, $target: expr, $($arg: tt)+) => {
+        $logger.log($crate::Log {
+            level: $crate::LogLevel::Critical,
+            target: $target.to_string(),
+            message: format!($($arg)+),
+            file: file!().to_string(),
+            line: line!()
+        });
+        std::process::exit(-1);
+    };
     ($target: expr, $($arg: tt)+) => {
         $crate::get_logger().as_mut().log($crate::Log {
             level: $crate::LogLevel::Critical,
@@ -79,6 +97,15 @@ macro_rules! critical {
 
 #[macro_export]
 macro_rules! error {
+    (logger: $logger: expr, $target: expr, $($arg: tt)+) => {
+        $logger.log($crate::Log {
+            level: $crate::LogLevel::Error,
+            target: $target.to_string(),
+            message: format!($($arg)+),
+            file: file!().to_string(),
+            line: line!()
+        });
+    };
     ($target: expr, $($arg: tt)+) => {
         $crate::get_logger().as_mut().log($crate::Log {
             level: $crate::LogLevel::Error,
@@ -92,6 +119,15 @@ macro_rules! error {
 
 #[macro_export]
 macro_rules! warn {
+    (logger: $logger: expr, $target: expr, $($arg: tt)+) => {
+        $logger.log($crate::Log {
+            level: $crate::LogLevel::Warning,
+            target: $target.to_string(),
+            message: format!($($arg)+),
+            file: file!().to_string(),
+            line: line!()
+        });
+    };
     ($target: expr, $($arg: tt)+) => {
         $crate::get_logger().as_mut().log($crate::Log {
             level: $crate::LogLevel::Warning,
@@ -105,6 +141,15 @@ macro_rules! warn {
 
 #[macro_export]
 macro_rules! info {
+    (logger: $logger: expr, $target: expr, $($arg: tt)+) => {
+        $logger.log($crate::Log {
+            level: $crate::LogLevel::Info,
+            target: $target.to_string(),
+            message: format!($($arg)+),
+            file: file!().to_string(),
+            line: line!()
+        });
+    };
     ($target: expr, $($arg: tt)+) => {
         $crate::get_logger().as_mut().log($crate::Log {
             level: $crate::LogLevel::Info,
@@ -118,6 +163,15 @@ macro_rules! info {
 
 #[macro_export]
 macro_rules! debug {
+    (logger: $logger: expr, $target: expr, $($arg: tt)+) => {
+        $logger.log($crate::Log {
+            level: $crate::LogLevel::Debug,
+            target: $target.to_string(),
+            message: format!($($arg)+),
+            file: file!().to_string(),
+            line: line!()
+        });
+    };
     ($target: expr, $($arg: tt)+) => {
         $crate::get_logger().as_mut().log($crate::Log {
             level: $crate::LogLevel::Debug,
@@ -0,0 +1,221 @@
+use std::cell::UnsafeCell;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+//                                         Triple Buffer                                          //
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+// The writer and the reader each own one of the three slots, the third one is shared and is
+// exchanged atomically, its index is stored along with a flag telling if it holds a new snapshot.
+const INDEX_MASK: u8 = 0b011;
+const DIRTY: u8 = 0b100;
+
+struct Shared<T> {
+    buffers: [UnsafeCell<T>; 3],
+    middle: AtomicU8,
+}
+
+// Each slot is only ever accessed by the side which currently owns its index. The snapshot
+// returned by `peek` is shared along with a `&BufferReader`, so `T` has to be `Sync` as well
+unsafe impl<T: Send + Sync> Sync for Shared<T> {}
+
+pub fn triple_buffer<T: Clone>(initial: T) -> (BufferWriter<T>, BufferReader<T>) {
+    let shared = Arc::new(Shared {
+        buffers: [UnsafeCell::new(initial.clone()), UnsafeCell::new(initial.clone()), UnsafeCell::new(initial)],
+        middle: AtomicU8::new(1),
+    });
+
+    let writer = BufferWriter {
+        shared: shared.clone(),
+        index: 0,
+    };
+    let reader = BufferReader {
+        shared,
+        index: 2,
+    };
+
+    return (writer, reader);
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+//                                         Buffer Writer                                          //
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+pub struct BufferWriter<T> {
+    shared: Arc<Shared<T>>,
+    index: u8,
+}
+
+impl<T> BufferWriter<T> {
+
+    // The back buffer still holds an older snapshot, it has to be fully rewritten before publishing
+    pub fn write(&mut self) -> &mut T {
+        unsafe {
+            return &mut *self.shared.buffers[self.index as usize].get();
+        }
+    }
+
+    pub fn publish(&mut self) {
+        let previous = self.shared.middle.swap(self.index | DIRTY, Ordering::AcqRel);
+        self.index = previous & INDEX_MASK;
+    }
+
+    pub fn set(&mut self, value: T) {
+        *self.write() = value;
+        self.publish();
+    }
+
+    // True while the last published snapshot has not been picked up by the reader
+    pub fn is_pending(&self) -> bool {
+        return self.shared.middle.load(Ordering::Acquire) & DIRTY != 0;
+    }
+
+}
+
+impl<T: Clone> BufferWriter<T> {
+
+    pub fn set_from(&mut self, value: &T) {
+        self.write().clone_from(value);
+        self.publish();
+    }
+
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+//                                         Buffer Reader                                          //
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+/// Readers of values which are not `Sync` can not be shared between threads:
+///
+/// ```compile_fail
+/// let (_writer, reader) = lead_mem::buffer::triple_buffer(std::cell::Cell::new(0));
+/// std::thread::scope(|scope| {
+///     scope.spawn(|| reader.peek().set(1));
+///     reader.peek().set(2);
+/// });
+/// ```
+pub struct BufferReader<T> {
+    shared: Arc<Shared<T>>,
+    index: u8,
+}
+
+impl<T> BufferReader<T> {
+
+    pub fn has_update(&self) -> bool {
+        return self.shared.middle.load(Ordering::Acquire) & DIRTY != 0;
+    }
+
+    // Switches to the latest published snapshot, returns false if nothing new was published
+    pub fn update(&mut self) -> bool {
+        if !self.has_update() {
+            return false;
+        }
+
+        let previous = self.shared.middle.swap(self.index, Ordering::AcqRel);
+        self.index = previous & INDEX_MASK;
+        return true;
+    }
+
+    pub fn read(&mut self) -> &T {
+        self.update();
+        return self.peek();
+    }
+
+    // Current snapshot, without looking for a newer one
+    pub fn peek(&self) -> &T {
+        unsafe {
+            return &*self.shared.buffers[self.index as usize].get();
+        }
+    }
+
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+//                                             Tests                                              //
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reader_starts_with_the_initial_value() {
+        let (writer, mut reader) = triple_buffer(7);
+        assert!(!writer.is_pending());
+        assert!(!reader.has_update());
+        assert!(!reader.update());
+        assert_eq!(*reader.read(), 7);
+    }
+
+    #[test]
+    fn published_values_are_read_once() {
+        let (mut writer, mut reader) = triple_buffer(0);
+
+        *writer.write() = 1;
+        assert!(!reader.has_update());
+        writer.publish();
+        assert!(writer.is_pending());
+        assert!(reader.has_update());
+        assert_eq!(*reader.peek(), 0);
+
+        assert_eq!(*reader.read(), 1);
+        assert!(!writer.is_pending());
+        assert!(!reader.update());
+        assert_eq!(*reader.read(), 1);
+    }
+
+    #[test]
+    fn reader_skips_to_the_latest_value() {
+        let (mut writer, mut reader) = triple_buffer(String::new());
+        writer.set("first".to_string());
+        writer.set("second".to_string());
+        writer.set_from(&"third".to_string());
+
+        assert_eq!(reader.read(), "third");
+        assert!(!reader.update());
+
+        writer.set("fourth".to_string());
+        assert_eq!(reader.read(), "fourth");
+    }
+
+    #[test]
+    fn writer_never_overwrites_the_read_value() {
+        let (mut writer, mut reader) = triple_buffer(0);
+        writer.set(1);
+        assert_eq!(*reader.read(), 1);
+
+        // The reader keeps its slot while the writer cycles through the other two
+        for value in 2..10 {
+            writer.set(value);
+            assert_eq!(*reader.peek(), 1);
+        }
+        assert_eq!(*reader.read(), 9);
+    }
+
+    #[test]
+    fn concurrent_reads_are_never_torn_or_stale() {
+        const COUNT: u64 = 100_000;
+        let (mut writer, mut reader) = triple_buffer([0u64; 16]);
+
+        let thread = std::thread::spawn(move || {
+            for value in 1..=COUNT {
+                writer.write().fill(value);
+                writer.publish();
+            }
+        });
+
+        let mut last = 0;
+        while last < COUNT {
+            let snapshot = reader.read();
+            let value = snapshot[0];
+            assert!(snapshot.iter().all(|other| *other == value), "torn read: {:?}", snapshot);
+            assert!(value >= last, "read {} after {}", value, last);
+            last = value;
+        }
+
+        thread.join().unwrap();
+        assert_eq!(reader.read()[0], COUNT);
+    }
+
+}
@@ -1,4 +1,8 @@
+pub mod buffer;
+pub mod lifecycle;
 pub mod pointer;
+pub mod resources;
+pub mod tracking;
 
 ////////////////////////////////////////////////////////////////////////////////////////////////////
 //                                          Object State                                          //
@@ -22,13 +26,16 @@ macro_rules! singleton_mut {
 
         pub fn $func() -> $crate::pointer::MutPointer<$type> {
             unsafe {
-                if let Some(value) = &mut $name {
+                if let Some(value) = &mut *std::ptr::addr_of_mut!($name) {
                     return $crate::pointer::MutPointer::new(value);
-                } else {
-                    $name = Some($init);
-                    return $func();
                 }
             }
+
+            let value = $init;
+            unsafe {
+                $name = Some(value);
+            }
+            return $func();
         }
     };
     ($name: ident, $type: tt, $init: expr) => {
@@ -36,13 +43,16 @@ macro_rules! singleton_mut {
 
         pub fn get() -> $crate::pointer::MutPointer<$type> {
             unsafe {
-                if let Some(value) = &mut $name {
+                if let Some(value) = &mut *std::ptr::addr_of_mut!($name) {
                     return $crate::pointer::MutPointer::new(value);
-                } else {
-                    $name = Some($init);
-                    return get();
                 }
             }
+
+            let value = $init;
+            unsafe {
+                $name = Some(value);
+            }
+            return get();
         }
     };
 }
@@ -54,13 +64,16 @@ macro_rules! singleton {
 
         pub fn $func() -> $crate::pointer::Pointer<$type> {
             unsafe {
-                if let Some(value) = &mut $name {
+                if let Some(value) = &mut *std::ptr::addr_of_mut!($name) {
                     return $crate::pointer::Pointer::new(value);
-                } else {
-                    $name = Some($init);
-                    return $func();
                 }
             }
+
+            let value = $init;
+            unsafe {
+                $name = Some(value);
+            }
+            return $func();
         }
     };
     ($name: ident, $type: tt, $init: expr) => {
@@ -68,13 +81,16 @@ macro_rules! singleton {
 
         pub fn get() -> $crate::pointer::Pointer<$type> {
             unsafe {
-                if let Some(value) = &mut $name {
+                if let Some(value) = &mut *std::ptr::addr_of_mut!($name) {
                     return $crate::pointer::Pointer::new(value);
-                } else {
-                    $name = Some($init);
-                    return get();
                 }
             }
+
+            let value = $init;
+            unsafe {
+                $name = Some(value);
+            }
+            return get();
         }
     };
 }
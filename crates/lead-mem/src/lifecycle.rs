@@ -0,0 +1,331 @@
+use crate::{ObjectState, singleton_mut};
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+//                                           Object Id                                            //
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub struct ObjectId(u64);
+
+impl Display for ObjectId {
+
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        return write!(f, "#{}", self.0);
+    }
+
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+//                                         Object Record                                          //
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+#[derive(Clone, Debug)]
+pub struct ObjectRecord {
+    pub id: ObjectId,
+    pub name: String,
+    pub state: ObjectState,
+    pub dispose_count: u32,
+    pub allow_skip_init: bool,
+}
+
+impl Display for ObjectRecord {
+
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        return write!(f, "'{}' ({}) in state {:?}", self.name, self.id, self.state);
+    }
+
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+//                                        Lifecycle Error                                         //
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub enum LifecycleError {
+    UnknownObject(ObjectId),
+    InvalidTransition {
+        id: ObjectId,
+        name: String,
+        from: ObjectState,
+        to: ObjectState,
+    },
+    AlreadyDisposed {
+        id: ObjectId,
+        name: String,
+    },
+}
+
+impl Display for LifecycleError {
+
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        return match self {
+            LifecycleError::UnknownObject(id) => write!(f, "Object {} is not registered", id),
+            LifecycleError::InvalidTransition { id, name, from, to } => write!(f, "Invalid transition of '{}' ({}) from {:?} to {:?}", name, id, from, to),
+            LifecycleError::AlreadyDisposed { id, name } => write!(f, "Object '{}' ({}) disposed more than once", name, id),
+        }
+    }
+
+}
+
+impl std::error::Error for LifecycleError {}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+//                                        Lifecycle Report                                        //
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+#[derive(Clone, Debug, Default)]
+pub struct LifecycleReport {
+    pub leaked: Vec<ObjectRecord>,
+    pub disposed_twice: Vec<ObjectRecord>,
+    pub errors: Vec<LifecycleError>,
+}
+
+impl LifecycleReport {
+
+    pub fn is_clean(&self) -> bool {
+        return self.leaked.is_empty() && self.disposed_twice.is_empty() && self.errors.is_empty();
+    }
+
+}
+
+impl Display for LifecycleReport {
+
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} leaked, {} disposed twice, {} invalid transitions",
+               self.leaked.len(), self.disposed_twice.len(), self.errors.len())?;
+
+        for record in self.leaked.iter() {
+            write!(f, "\n  Leaked: {}", record)?;
+        }
+        for record in self.disposed_twice.iter() {
+            write!(f, "\n  Disposed {} times: {}", record.dispose_count, record)?;
+        }
+        for error in self.errors.iter() {
+            write!(f, "\n  {}", error)?;
+        }
+
+        return Ok(());
+    }
+
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+//                                       Lifecycle Registry                                       //
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+singleton_mut!(func: get_lifecycle_registry, LIFECYCLE_REGISTRY, LifecycleRegistry, LifecycleRegistry::new());
+
+pub struct LifecycleRegistry {
+    objects: BTreeMap<ObjectId, ObjectRecord>,
+    errors: Vec<LifecycleError>,
+    next_id: u64,
+}
+
+impl LifecycleRegistry {
+
+    pub fn new() -> LifecycleRegistry {
+        return LifecycleRegistry {
+            objects: BTreeMap::new(),
+            errors: Vec::new(),
+            next_id: 0,
+        }
+    }
+
+    pub fn register(&mut self, name: &str) -> ObjectId {
+        let id = ObjectId(self.next_id);
+        self.next_id += 1;
+
+        self.objects.insert(id, ObjectRecord {
+            id,
+            name: name.to_string(),
+            state: ObjectState::Created,
+            dispose_count: 0,
+            allow_skip_init: false,
+        });
+
+        return id;
+    }
+
+    // Objects which may legitimately be disposed without ever being initialized
+    pub fn allow_skip_init(&mut self, id: ObjectId) {
+        if let Some(record) = self.objects.get_mut(&id) {
+            record.allow_skip_init = true;
+        }
+    }
+
+    pub fn unregister(&mut self, id: ObjectId) -> Option<ObjectRecord> {
+        return self.objects.remove(&id);
+    }
+
+    pub fn transition(&mut self, id: ObjectId, to: ObjectState) -> Result<(), LifecycleError> {
+        let result = match self.objects.get_mut(&id) {
+            Some(record) => Self::apply(record, to),
+            None => Err(LifecycleError::UnknownObject(id)),
+        };
+
+        if let Err(err) = &result {
+            self.errors.push(err.clone());
+        }
+
+        return result;
+    }
+
+    fn apply(record: &mut ObjectRecord, to: ObjectState) -> Result<(), LifecycleError> {
+        if to == ObjectState::Disposed {
+            record.dispose_count += 1;
+        }
+
+        let valid = match (record.state, to) {
+            (ObjectState::Created, ObjectState::Initialized) => true,
+            (ObjectState::Created, ObjectState::Disposed) => record.allow_skip_init,
+            (ObjectState::Initialized, ObjectState::Disposed) => true,
+            (ObjectState::Disposed, ObjectState::Disposed) => {
+                return Err(LifecycleError::AlreadyDisposed {
+                    id: record.id,
+                    name: record.name.clone(),
+                });
+            }
+            _ => false,
+        };
+
+        if !valid {
+            return Err(LifecycleError::InvalidTransition {
+                id: record.id,
+                name: record.name.clone(),
+                from: record.state,
+                to,
+            });
+        }
+
+        record.state = to;
+        return Ok(());
+    }
+
+    pub fn clear(&mut self) {
+        self.objects.clear();
+        self.errors.clear();
+    }
+
+    /* ====================================== Getters ======================================= */
+
+    pub fn get_state(&self, id: ObjectId) -> Option<ObjectState> {
+        return self.objects.get(&id).map(|record| record.state);
+    }
+
+    pub fn get_record(&self, id: ObjectId) -> Option<&ObjectRecord> {
+        return self.objects.get(&id);
+    }
+
+    pub fn records(&self) -> impl Iterator<Item = &ObjectRecord> {
+        return self.objects.values();
+    }
+
+    pub fn get_errors(&self) -> &[LifecycleError] {
+        return &self.errors;
+    }
+
+    pub fn report(&self) -> LifecycleReport {
+        return LifecycleReport {
+            leaked: self.objects.values().filter(|record| record.state != ObjectState::Disposed).cloned().collect(),
+            disposed_twice: self.objects.values().filter(|record| record.dispose_count > 1).cloned().collect(),
+            errors: self.errors.iter().filter(|err| !matches!(err, LifecycleError::AlreadyDisposed { .. })).cloned().collect(),
+        }
+    }
+
+}
+
+impl Default for LifecycleRegistry {
+
+    fn default() -> Self {
+        return LifecycleRegistry::new();
+    }
+
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+//                                             Tests                                              //
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn objects_go_through_their_lifecycle() {
+        let mut registry = LifecycleRegistry::new();
+        let id = registry.register("Texture");
+        assert_eq!(registry.get_state(id), Some(ObjectState::Created));
+
+        assert_eq!(registry.transition(id, ObjectState::Initialized), Ok(()));
+        assert_eq!(registry.transition(id, ObjectState::Disposed), Ok(()));
+        assert_eq!(registry.get_state(id), Some(ObjectState::Disposed));
+        assert!(registry.report().is_clean());
+    }
+
+    #[test]
+    fn skipping_init_has_to_be_allowed() {
+        let mut registry = LifecycleRegistry::new();
+        let strict = registry.register("Shader");
+        let lazy = registry.register("Sound");
+        registry.allow_skip_init(lazy);
+
+        assert_eq!(registry.transition(strict, ObjectState::Disposed), Err(LifecycleError::InvalidTransition {
+            id: strict,
+            name: "Shader".to_string(),
+            from: ObjectState::Created,
+            to: ObjectState::Disposed,
+        }));
+        assert_eq!(registry.get_state(strict), Some(ObjectState::Created));
+        assert_eq!(registry.transition(lazy, ObjectState::Disposed), Ok(()));
+
+        let report = registry.report();
+        assert_eq!(report.errors.len(), 1);
+        assert_eq!(report.leaked.iter().map(|record| record.id).collect::<Vec<_>>(), [strict]);
+    }
+
+    #[test]
+    fn double_disposes_are_reported_separately() {
+        let mut registry = LifecycleRegistry::new();
+        let id = registry.register("Buffer");
+        registry.transition(id, ObjectState::Initialized).unwrap();
+        registry.transition(id, ObjectState::Disposed).unwrap();
+
+        assert_eq!(registry.transition(id, ObjectState::Disposed), Err(LifecycleError::AlreadyDisposed { id, name: "Buffer".to_string() }));
+        let report = registry.report();
+        assert!(report.errors.is_empty());
+        assert!(report.leaked.is_empty());
+        assert_eq!(report.disposed_twice.len(), 1);
+        assert_eq!(report.disposed_twice[0].dispose_count, 2);
+        assert!(!report.is_clean());
+        assert_eq!(registry.get_errors().len(), 1);
+    }
+
+    #[test]
+    fn leaks_appear_in_the_report() {
+        let mut registry = LifecycleRegistry::new();
+        let created = registry.register("Created");
+        let initialized = registry.register("Initialized");
+        registry.transition(initialized, ObjectState::Initialized).unwrap();
+
+        let report = registry.report();
+        assert_eq!(report.leaked.iter().map(|record| record.id).collect::<Vec<_>>(), [created, initialized]);
+        assert!(report.to_string().starts_with("2 leaked, 0 disposed twice, 0 invalid transitions"));
+        assert!(report.to_string().contains("Leaked: 'Initialized' (#1) in state Initialized"));
+
+        registry.unregister(created);
+        assert_eq!(registry.report().leaked.len(), 1);
+    }
+
+    #[test]
+    fn unknown_objects_are_rejected() {
+        let mut registry = LifecycleRegistry::new();
+        let id = registry.register("Window");
+        registry.unregister(id);
+
+        assert_eq!(registry.transition(id, ObjectState::Initialized), Err(LifecycleError::UnknownObject(id)));
+        assert_eq!(registry.report().errors, [LifecycleError::UnknownObject(id)]);
+    }
+
+}
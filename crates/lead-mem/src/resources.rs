@@ -0,0 +1,213 @@
+use std::any::{Any, TypeId};
+use std::cell::{Ref, RefCell, RefMut};
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+//                                         Resource Error                                         //
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum ResourceError {
+    NotFound(&'static str),
+    AlreadyBorrowed(&'static str),
+    AlreadyBorrowedMut(&'static str),
+}
+
+impl Display for ResourceError {
+
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        return match self {
+            ResourceError::NotFound(name) => write!(f, "Resource '{}' not found", name),
+            ResourceError::AlreadyBorrowed(name) => write!(f, "Resource '{}' is already borrowed", name),
+            ResourceError::AlreadyBorrowedMut(name) => write!(f, "Resource '{}' is already mutably borrowed", name),
+        }
+    }
+
+}
+
+impl std::error::Error for ResourceError {}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+//                                           Resources                                            //
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+pub struct Resources {
+    entries: HashMap<TypeId, RefCell<Box<dyn Any>>>,
+}
+
+impl Resources {
+
+    pub fn new() -> Resources {
+        return Resources {
+            entries: HashMap::new(),
+        }
+    }
+
+    pub fn insert<T: 'static>(&mut self, resource: T) -> Option<T> {
+        let previous = self.entries.insert(TypeId::of::<T>(), RefCell::new(Box::new(resource)));
+        return previous.and_then(|cell| cell.into_inner().downcast::<T>().ok()).map(|boxed| *boxed);
+    }
+
+    pub fn remove<T: 'static>(&mut self) -> Option<T> {
+        let cell = self.entries.remove(&TypeId::of::<T>())?;
+        return cell.into_inner().downcast::<T>().ok().map(|boxed| *boxed);
+    }
+
+    pub fn contains<T: 'static>(&self) -> bool {
+        return self.entries.contains_key(&TypeId::of::<T>());
+    }
+
+    pub fn len(&self) -> usize {
+        return self.entries.len();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        return self.entries.is_empty();
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    /* ======================================= Borrow ======================================= */
+
+    pub fn try_get<T: 'static>(&self) -> Result<Ref<'_, T>, ResourceError> {
+        let name = std::any::type_name::<T>();
+        let cell = self.entries.get(&TypeId::of::<T>()).ok_or(ResourceError::NotFound(name))?;
+        let borrowed = cell.try_borrow().map_err(|_| ResourceError::AlreadyBorrowedMut(name))?;
+        return Ok(Ref::map(borrowed, |resource| resource.downcast_ref::<T>().unwrap()));
+    }
+
+    pub fn try_get_mut<T: 'static>(&self) -> Result<RefMut<'_, T>, ResourceError> {
+        let name = std::any::type_name::<T>();
+        let cell = self.entries.get(&TypeId::of::<T>()).ok_or(ResourceError::NotFound(name))?;
+        let borrowed = cell.try_borrow_mut().map_err(|_| ResourceError::AlreadyBorrowed(name))?;
+        return Ok(RefMut::map(borrowed, |resource| resource.downcast_mut::<T>().unwrap()));
+    }
+
+    // Panics if the resource is currently mutably borrowed
+    pub fn get<T: 'static>(&self) -> Option<Ref<'_, T>> {
+        return match self.try_get::<T>() {
+            Ok(resource) => Some(resource),
+            Err(ResourceError::NotFound(_)) => None,
+            Err(err) => panic!("{}", err),
+        }
+    }
+
+    // Panics if the resource is currently borrowed
+    pub fn get_mut<T: 'static>(&self) -> Option<RefMut<'_, T>> {
+        return match self.try_get_mut::<T>() {
+            Ok(resource) => Some(resource),
+            Err(ResourceError::NotFound(_)) => None,
+            Err(err) => panic!("{}", err),
+        }
+    }
+
+    // Direct access without runtime borrow tracking, exclusive access is guaranteed by `&mut self`
+    pub fn get_exclusive<T: 'static>(&mut self) -> Option<&mut T> {
+        let cell = self.entries.get_mut(&TypeId::of::<T>())?;
+        return cell.get_mut().downcast_mut::<T>();
+    }
+
+    pub fn get_or_insert_with<T: 'static, F: FnOnce() -> T>(&mut self, init: F) -> RefMut<'_, T> {
+        let cell = self.entries.entry(TypeId::of::<T>()).or_insert_with(|| RefCell::new(Box::new(init())));
+        return RefMut::map(cell.borrow_mut(), |resource| resource.downcast_mut::<T>().unwrap());
+    }
+
+}
+
+impl Default for Resources {
+
+    fn default() -> Self {
+        return Resources::new();
+    }
+
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+//                                             Tests                                              //
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(PartialEq, Debug)]
+    struct Score(u32);
+
+    #[derive(PartialEq, Debug)]
+    struct Level(&'static str);
+
+    #[test]
+    fn resources_are_stored_by_type() {
+        let mut resources = Resources::new();
+        assert_eq!(resources.insert(Score(1)), None);
+        assert_eq!(resources.insert(Level("intro")), None);
+        assert_eq!(resources.len(), 2);
+
+        assert_eq!(resources.insert(Score(2)), Some(Score(1)));
+        assert_eq!(*resources.get::<Score>().unwrap(), Score(2));
+        resources.get_mut::<Score>().unwrap().0 += 1;
+        assert_eq!(resources.get_exclusive::<Score>(), Some(&mut Score(3)));
+
+        assert_eq!(resources.remove::<Level>(), Some(Level("intro")));
+        assert_eq!(resources.remove::<Level>(), None);
+        assert!(!resources.contains::<Level>());
+        assert!(resources.get::<Level>().is_none());
+
+        resources.clear();
+        assert!(resources.is_empty());
+    }
+
+    #[test]
+    fn borrow_conflicts_are_reported() {
+        let mut resources = Resources::new();
+        resources.insert(Score(0));
+        let name = std::any::type_name::<Score>();
+
+        {
+            let _first = resources.try_get::<Score>().unwrap();
+            let _second = resources.try_get::<Score>().unwrap();
+            assert_eq!(resources.try_get_mut::<Score>().err(), Some(ResourceError::AlreadyBorrowed(name)));
+        }
+        {
+            let _writer = resources.try_get_mut::<Score>().unwrap();
+            assert_eq!(resources.try_get::<Score>().err(), Some(ResourceError::AlreadyBorrowedMut(name)));
+            assert_eq!(resources.try_get_mut::<Score>().err(), Some(ResourceError::AlreadyBorrowed(name)));
+            assert!(resources.try_get::<Level>().is_err());
+        }
+
+        assert_eq!(resources.try_get::<Level>().err(), Some(ResourceError::NotFound(std::any::type_name::<Level>())));
+        assert!(resources.try_get_mut::<Score>().is_ok());
+    }
+
+    #[test]
+    #[should_panic(expected = "Resource 'lead_mem::resources::tests::Score' is already mutably borrowed")]
+    fn get_panics_while_mutably_borrowed() {
+        let mut resources = Resources::new();
+        resources.insert(Score(0));
+
+        let _writer = resources.get_mut::<Score>();
+        resources.get::<Score>();
+    }
+
+    #[test]
+    fn get_or_insert_with_initializes_once() {
+        let mut resources = Resources::new();
+        let mut calls = 0;
+
+        resources.get_or_insert_with(|| {
+            calls += 1;
+            return Score(1);
+        }).0 += 1;
+        let score = resources.get_or_insert_with(|| {
+            calls += 1;
+            return Score(10);
+        }).0;
+
+        assert_eq!(score, 2);
+        assert_eq!(calls, 1);
+    }
+
+}
@@ -0,0 +1,509 @@
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+use std::fmt::{Display, Formatter};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+//                                            Counters                                            //
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+pub const MAX_TAGS: usize = 64;
+pub const UNTAGGED: &str = "Untagged";
+
+struct Counters {
+    allocations: AtomicUsize,
+    deallocations: AtomicUsize,
+    live: AtomicUsize,
+    peak: AtomicUsize,
+    total: AtomicUsize,
+}
+
+impl Counters {
+
+    const fn new() -> Counters {
+        return Counters {
+            allocations: AtomicUsize::new(0),
+            deallocations: AtomicUsize::new(0),
+            live: AtomicUsize::new(0),
+            peak: AtomicUsize::new(0),
+            total: AtomicUsize::new(0),
+        }
+    }
+
+    fn on_alloc(&self, size: usize) {
+        self.allocations.fetch_add(1, Ordering::Relaxed);
+        self.total.fetch_add(size, Ordering::Relaxed);
+        let live = self.live.fetch_add(size, Ordering::Relaxed) + size;
+        self.peak.fetch_max(live, Ordering::Relaxed);
+    }
+
+    fn on_dealloc(&self, size: usize) {
+        self.deallocations.fetch_add(1, Ordering::Relaxed);
+        self.live.fetch_sub(size, Ordering::Relaxed);
+    }
+
+    fn on_grow(&self, size: usize) {
+        self.total.fetch_add(size, Ordering::Relaxed);
+        let live = self.live.fetch_add(size, Ordering::Relaxed) + size;
+        self.peak.fetch_max(live, Ordering::Relaxed);
+    }
+
+    fn on_shrink(&self, size: usize) {
+        self.live.fetch_sub(size, Ordering::Relaxed);
+    }
+
+    fn snapshot(&self) -> MemoryStats {
+        return MemoryStats {
+            allocations: self.allocations.load(Ordering::Relaxed),
+            deallocations: self.deallocations.load(Ordering::Relaxed),
+            live: self.live.load(Ordering::Relaxed),
+            peak: self.peak.load(Ordering::Relaxed),
+            total: self.total.load(Ordering::Relaxed),
+        }
+    }
+
+}
+
+#[allow(clippy::declare_interior_mutable_const)]
+const EMPTY_COUNTERS: Counters = Counters::new();
+
+static TRACKING: AtomicBool = AtomicBool::new(false);
+static GLOBAL: Counters = Counters::new();
+static TAG_COUNTERS: [Counters; MAX_TAGS] = [EMPTY_COUNTERS; MAX_TAGS];
+static TAG_NAMES: Mutex<Vec<&'static str>> = Mutex::new(Vec::new());
+static LAST_FRAME: Mutex<Option<MemoryReport>> = Mutex::new(None);
+
+thread_local! {
+    static CURRENT_TAG: Cell<usize> = const { Cell::new(0) };
+}
+
+fn current_tag() -> usize {
+    return CURRENT_TAG.try_with(|tag| tag.get()).unwrap_or(0);
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+//                                       Tracking Allocator                                       //
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+// Every block is prefixed with a header storing the tag it was allocated under, so that frees are
+// credited back to the right tag even when they happen in another scope or thread.
+const HEADER: usize = std::mem::size_of::<usize>();
+
+pub struct TrackingAllocator<A: GlobalAlloc = System> {
+    inner: A,
+}
+
+impl TrackingAllocator<System> {
+
+    pub const fn new() -> TrackingAllocator<System> {
+        return TrackingAllocator {
+            inner: System
+        }
+    }
+
+}
+
+impl Default for TrackingAllocator<System> {
+
+    fn default() -> Self {
+        return TrackingAllocator::new();
+    }
+
+}
+
+impl<A: GlobalAlloc> TrackingAllocator<A> {
+
+    pub const fn wrap(inner: A) -> TrackingAllocator<A> {
+        return TrackingAllocator {
+            inner
+        }
+    }
+
+    fn offset(layout: &Layout) -> usize {
+        return layout.align().max(HEADER);
+    }
+
+    fn outer_layout(layout: &Layout) -> Option<Layout> {
+        let size = layout.size().checked_add(Self::offset(layout))?;
+        return Layout::from_size_align(size, layout.align().max(std::mem::align_of::<usize>())).ok();
+    }
+
+    unsafe fn finish_alloc(&self, base: *mut u8, layout: &Layout) -> *mut u8 {
+        if base.is_null() {
+            return base;
+        }
+
+        let tag = current_tag();
+        let ptr = base.add(Self::offset(layout));
+        (ptr.sub(HEADER) as *mut usize).write_unaligned(tag);
+
+        TRACKING.store(true, Ordering::Relaxed);
+        GLOBAL.on_alloc(layout.size());
+        TAG_COUNTERS[tag].on_alloc(layout.size());
+        return ptr;
+    }
+
+}
+
+unsafe impl<A: GlobalAlloc> GlobalAlloc for TrackingAllocator<A> {
+
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        return match Self::outer_layout(&layout) {
+            Some(outer) => self.finish_alloc(self.inner.alloc(outer), &layout),
+            None => std::ptr::null_mut(),
+        }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        let tag = (ptr.sub(HEADER) as *const usize).read_unaligned();
+        GLOBAL.on_dealloc(layout.size());
+        TAG_COUNTERS[tag].on_dealloc(layout.size());
+
+        if let Some(outer) = Self::outer_layout(&layout) {
+            self.inner.dealloc(ptr.sub(Self::offset(&layout)), outer);
+        }
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        return match Self::outer_layout(&layout) {
+            Some(outer) => self.finish_alloc(self.inner.alloc_zeroed(outer), &layout),
+            None => std::ptr::null_mut(),
+        }
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let offset = Self::offset(&layout);
+        let (outer, outer_size) = match (Self::outer_layout(&layout), new_size.checked_add(offset)) {
+            (Some(outer), Some(outer_size)) => (outer, outer_size),
+            _ => return std::ptr::null_mut(),
+        };
+
+        let base = self.inner.realloc(ptr.sub(offset), outer, outer_size);
+        if base.is_null() {
+            return base;
+        }
+
+        // The header moves along with the block, so the original tag keeps ownership
+        let ptr = base.add(offset);
+        let tag = (ptr.sub(HEADER) as *const usize).read_unaligned();
+        if new_size >= layout.size() {
+            GLOBAL.on_grow(new_size - layout.size());
+            TAG_COUNTERS[tag].on_grow(new_size - layout.size());
+        } else {
+            GLOBAL.on_shrink(layout.size() - new_size);
+            TAG_COUNTERS[tag].on_shrink(layout.size() - new_size);
+        }
+        return ptr;
+    }
+
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+//                                              Tags                                              //
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+fn tag_index(name: &'static str) -> usize {
+    let mut names = TAG_NAMES.lock().unwrap_or_else(|err| err.into_inner());
+    if names.is_empty() {
+        names.push(UNTAGGED);
+    }
+
+    if let Some(index) = names.iter().position(|tag| *tag == name) {
+        return index;
+    }
+
+    // Once every slot is used, new tags are accounted as untagged
+    if names.len() >= MAX_TAGS {
+        return 0;
+    }
+
+    names.push(name);
+    return names.len() - 1;
+}
+
+pub struct TagScope {
+    previous: usize,
+}
+
+impl Drop for TagScope {
+
+    fn drop(&mut self) {
+        let _ = CURRENT_TAG.try_with(|tag| tag.set(self.previous));
+    }
+
+}
+
+#[must_use = "the tag is only active while the returned scope is alive"]
+pub fn scope(name: &'static str) -> TagScope {
+    let index = tag_index(name);
+    let previous = current_tag();
+    let _ = CURRENT_TAG.try_with(|tag| tag.set(index));
+
+    return TagScope {
+        previous
+    }
+}
+
+pub fn current_tag_name() -> &'static str {
+    let names = TAG_NAMES.lock().unwrap_or_else(|err| err.into_inner());
+    return names.get(current_tag()).copied().unwrap_or(UNTAGGED);
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+//                                             Stats                                              //
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+#[derive(Copy, Clone, Eq, PartialEq, Default, Debug)]
+pub struct MemoryStats {
+    pub allocations: usize,
+    pub deallocations: usize,
+    pub live: usize,
+    pub peak: usize,
+    pub total: usize,
+}
+
+impl MemoryStats {
+
+    pub fn live_allocations(&self) -> usize {
+        return self.allocations.saturating_sub(self.deallocations);
+    }
+
+}
+
+#[derive(Clone, Debug)]
+pub struct TagStats {
+    pub name: &'static str,
+    pub stats: MemoryStats,
+    pub frame_allocations: usize,
+    pub frame_bytes: usize,
+}
+
+#[derive(Clone, Debug)]
+pub struct MemoryReport {
+    pub frame: u64,
+    pub global: MemoryStats,
+    pub frame_allocations: usize,
+    pub frame_bytes: usize,
+    pub tags: Vec<TagStats>,
+}
+
+impl MemoryReport {
+
+    pub fn get_tag(&self, name: &str) -> Option<&TagStats> {
+        return self.tags.iter().find(|tag| tag.name == name);
+    }
+
+}
+
+impl Display for MemoryReport {
+
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Frame {}: {} live in {} allocations (peak {}), {} allocations / {} this frame",
+                 self.frame, format_bytes(self.global.live), self.global.live_allocations(),
+                 format_bytes(self.global.peak), self.frame_allocations, format_bytes(self.frame_bytes))?;
+
+        for tag in self.tags.iter() {
+            writeln!(f, "  {:<16} {:>10} live {:>8} allocations (peak {}), {} allocations / {} this frame",
+                     tag.name, format_bytes(tag.stats.live), tag.stats.live_allocations(),
+                     format_bytes(tag.stats.peak), tag.frame_allocations, format_bytes(tag.frame_bytes))?;
+        }
+
+        return Ok(());
+    }
+
+}
+
+fn format_bytes(bytes: usize) -> String {
+    const UNITS: [&str; 4] = ["B", "KiB", "MiB", "GiB"];
+
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+
+    if unit == 0 {
+        return format!("{} {}", bytes, UNITS[unit]);
+    }
+    return format!("{:.2} {}", value, UNITS[unit]);
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+//                                            Reports                                             //
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+pub fn is_tracking() -> bool {
+    return TRACKING.load(Ordering::Relaxed);
+}
+
+pub fn stats() -> MemoryStats {
+    return GLOBAL.snapshot();
+}
+
+pub fn tag_stats(name: &str) -> Option<MemoryStats> {
+    let names = TAG_NAMES.lock().unwrap_or_else(|err| err.into_inner());
+    let index = names.iter().position(|tag| *tag == name)?;
+    return Some(TAG_COUNTERS[index].snapshot());
+}
+
+// Builds a report relative to the previous frame, without making it the new reference frame
+pub fn report() -> MemoryReport {
+    let last = LAST_FRAME.lock().unwrap_or_else(|err| err.into_inner());
+    return build_report(last.as_ref());
+}
+
+// Closes the current frame, the returned report stays available through `last_frame`
+pub fn end_frame() -> MemoryReport {
+    let mut last = LAST_FRAME.lock().unwrap_or_else(|err| err.into_inner());
+    let report = build_report(last.as_ref());
+    *last = Some(report.clone());
+    return report;
+}
+
+pub fn last_frame() -> Option<MemoryReport> {
+    return LAST_FRAME.lock().unwrap_or_else(|err| err.into_inner()).clone();
+}
+
+fn build_report(last: Option<&MemoryReport>) -> MemoryReport {
+    let names: Vec<&'static str> = TAG_NAMES.lock().unwrap_or_else(|err| err.into_inner()).clone();
+    let names = if names.is_empty() { vec![UNTAGGED] } else { names };
+
+    let mut tags = Vec::with_capacity(names.len());
+
+    let global = GLOBAL.snapshot();
+    let (frame, last_global) = match last {
+        Some(last) => (last.frame + 1, last.global),
+        None => (0, MemoryStats::default()),
+    };
+
+    for (index, name) in names.into_iter().enumerate() {
+        let stats = TAG_COUNTERS[index].snapshot();
+        let last_stats = last.and_then(|last| last.get_tag(name)).map(|tag| tag.stats).unwrap_or_default();
+        tags.push(TagStats {
+            name,
+            stats,
+            frame_allocations: stats.allocations - last_stats.allocations,
+            frame_bytes: stats.total - last_stats.total,
+        });
+    }
+
+    return MemoryReport {
+        frame,
+        global,
+        frame_allocations: global.allocations - last_global.allocations,
+        frame_bytes: global.total - last_global.total,
+        tags,
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+//                                             Tests                                              //
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[global_allocator]
+    static ALLOCATOR: TrackingAllocator = TrackingAllocator::new();
+
+    // Tests run in parallel and share the global counters, so each one checks its own tag
+    fn stats_of(name: &str) -> MemoryStats {
+        return tag_stats(name).unwrap_or_default();
+    }
+
+    #[test]
+    fn allocations_are_credited_to_the_current_tag() {
+        let outer = {
+            let _scope = scope("TestOuter");
+            let outer = Box::new([0u8; 100]);
+            {
+                let _scope = scope("TestInner");
+                let inner = Box::new([0u8; 40]);
+                assert_eq!(current_tag_name(), "TestInner");
+                assert_eq!(stats_of("TestInner").live, 40);
+                drop(inner);
+            }
+            assert_eq!(current_tag_name(), "TestOuter");
+            outer
+        };
+
+        let stats = stats_of("TestOuter");
+        assert_eq!(stats.allocations, 1);
+        assert_eq!(stats.live, 100);
+
+        // Freed outside of the scope, the bytes still go back to the tag they were allocated under
+        drop(outer);
+        let stats = stats_of("TestOuter");
+        assert_eq!(stats.deallocations, 1);
+        assert_eq!(stats.live, 0);
+        assert_eq!(stats.peak, 100);
+        assert_eq!(stats_of("TestInner").live, 0);
+    }
+
+    #[test]
+    fn frees_on_another_thread_are_credited_to_the_allocating_tag() {
+        let block = {
+            let _scope = scope("TestThread");
+            vec![0u8; 64]
+        };
+
+        std::thread::spawn(move || drop(block)).join().unwrap();
+        let stats = stats_of("TestThread");
+        assert_eq!(stats.live, 0);
+        assert_eq!(stats.live_allocations(), 0);
+    }
+
+    #[test]
+    fn reallocations_keep_the_live_bytes_balanced() {
+        let _scope = scope("TestRealloc");
+        let mut bytes: Vec<u8> = Vec::with_capacity(16);
+        assert_eq!(stats_of("TestRealloc").live, 16);
+
+        bytes.reserve_exact(1024);
+        let grown = bytes.capacity();
+        let stats = stats_of("TestRealloc");
+        assert_eq!(stats.allocations, 1);
+        assert_eq!(stats.live, grown);
+        assert_eq!(stats.total, grown);
+
+        bytes.shrink_to(8);
+        let shrunk = bytes.capacity();
+        let stats = stats_of("TestRealloc");
+        assert_eq!(stats.live, shrunk);
+        assert_eq!(stats.peak, grown);
+
+        drop(bytes);
+        let stats = stats_of("TestRealloc");
+        assert_eq!(stats.allocations, stats.deallocations);
+        assert_eq!(stats.live, 0);
+    }
+
+    #[test]
+    fn end_frame_starts_a_new_frame() {
+        // Building a report allocates, so only the block is allocated under the tag
+        let first = end_frame();
+        let block = {
+            let _scope = scope("TestFrame");
+            vec![0u8; 32]
+        };
+
+        let report = end_frame();
+        assert_eq!(report.frame, first.frame + 1);
+        let tag = report.get_tag("TestFrame").unwrap();
+        assert_eq!(tag.frame_allocations, 1);
+        assert_eq!(tag.frame_bytes, 32);
+        assert_eq!(last_frame().unwrap().frame, report.frame);
+
+        // Nothing was allocated under the tag since the previous frame
+        drop(block);
+        let report = end_frame();
+        let tag = report.get_tag("TestFrame").unwrap();
+        assert_eq!(tag.frame_allocations, 0);
+        assert_eq!(tag.frame_bytes, 0);
+        assert_eq!(tag.stats.live, 0);
+    }
+
+}
@@ -1,40 +1,38 @@
-use lead_core::state::{get_state_manager, TState};
-use lead_core::input::get_input;
-use lead_core::start;
+use lead_core::engine::{Context, Engine};
+use lead_core::state::TState;
 
 pub struct MainState;
 
 impl TState for MainState {
-    fn init(&mut self) {
+    fn init(&mut self, _ctx: &mut Context) {
         println!("Initialized");
     }
 
-    fn open(&mut self) {
+    fn open(&mut self, _ctx: &mut Context) {
         println!("Opened");
     }
 
-    fn update(&mut self, delta: f64) {
-        let input = get_input();
-        let mouse = input.as_mut().mouse();
+    fn update(&mut self, ctx: &mut Context, delta: f64) {
+        let mouse = ctx.input().mouse();
 
         if mouse.is_scroll_y() {
             println!("FPS: {}", 1.0 / delta);
         }
     }
 
-    fn close(&mut self) {
+    fn close(&mut self, _ctx: &mut Context) {
         println!("Closed");
     }
 
-    fn dispose(&mut self) {
+    fn dispose(&mut self, _ctx: &mut Context) {
         println!("Disposed");
     }
 }
 
 fn main() {
-    let state_manager = get_state_manager();
-    state_manager.as_mut().register(MainState);
-    state_manager.as_mut().open::<MainState>();
+    let mut engine = Engine::new();
+    engine.register(MainState);
+    engine.open::<MainState>();
 
-    start();
+    engine.run();
 }